@@ -1,14 +1,47 @@
 "use client";
 
-import { Gauge, Bug } from "lucide-react";
+import { useEffect, useState } from "react";
+import { Gauge, Bug, FileArchive } from "lucide-react";
 import { NetworkTopologyCard } from "@/components/diagnostic/network-topology-card";
 import { Toolbar } from "@/components/ui/toolbar";
 import { PingStatusCard } from "@/components/diagnostic/ping-status-card";
 import { BugReportDialog } from "@/components/diagnostic/bug-report-dialog";
 import { Button } from "@/components/ui/button";
 import { AnnouncementBar } from "@/components/ui/announcement-bar";
+import { toast } from "@/hooks/use-toast";
+import { appErrorMessage } from "@/lib/utils/app-error";
 
 export default function DiagnosticPage() {
+  const [isDesktop, setIsDesktop] = useState(false);
+  const [isSaving, setIsSaving] = useState(false);
+
+  useEffect(() => {
+    setIsDesktop(typeof window !== "undefined" && !!window.__TAURI__);
+  }, []);
+
+  const saveDiagnosticsBundle = async () => {
+    setIsSaving(true);
+    try {
+      const path = await window.__TAURI__.core.invoke<string>(
+        "create_diagnostics_bundle",
+      );
+      toast({
+        icon: "📦",
+        title: "Diagnostics bundle saved",
+        description: path,
+      });
+    } catch (error) {
+      toast({
+        icon: "❌",
+        title: "Failed to save diagnostics bundle",
+        description: appErrorMessage(error),
+        variant: "destructive",
+      });
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
   return (
     <div className="flex h-full flex-col">
       <AnnouncementBar variant="warning">
@@ -19,14 +52,27 @@ export default function DiagnosticPage() {
         title="Diagnostic"
         icon={<Gauge className="h-5 w-5" />}
         rightSection={
-          <BugReportDialog
-            trigger={
-              <Button variant="outline" size="sm">
-                <Bug className="h-4 w-4" />
-                Report Bug
+          <div className="flex gap-2">
+            {isDesktop && (
+              <Button
+                variant="outline"
+                size="sm"
+                onClick={saveDiagnosticsBundle}
+                disabled={isSaving}
+              >
+                <FileArchive className="h-4 w-4" />
+                {isSaving ? "Saving..." : "Save Diagnostics"}
               </Button>
-            }
-          />
+            )}
+            <BugReportDialog
+              trigger={
+                <Button variant="outline" size="sm">
+                  <Bug className="h-4 w-4" />
+                  Report Bug
+                </Button>
+              }
+            />
+          </div>
         }
       />
       <div className="space-y-6 overflow-auto p-6">
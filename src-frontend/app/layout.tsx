@@ -18,6 +18,9 @@ import { useEffect } from "react";
 import { useTheme } from "next-themes";
 import { useBreadcrumbStore } from "@/stores";
 import { DeepLinkRouter } from "@/components/deep-link-router";
+import { DaemonCredentialsListener } from "@/components/daemon-credentials-listener";
+import { RemoteAccessBanner } from "@/components/remote-access-banner";
+import { CrashReportListener } from "@/components/crash-report-listener";
 
 const title = "SyftBox";
 const description = "The internet of private data!";
@@ -86,6 +89,7 @@ function MainLayout({ children }: { children: React.ReactNode }) {
       >
         <div className="bg-sidebar flex h-screen w-screen flex-col">
           <TitleBar>{breadcrumbContent}</TitleBar>
+          <RemoteAccessBanner />
           <div className="flex flex-1 overflow-hidden">
             <Sidebar className="border-none">
               <AppSidebar />
@@ -123,6 +127,8 @@ export default function RootLayout({
             disableTransitionOnChange
           >
             <DeepLinkRouter />
+            <DaemonCredentialsListener />
+            <CrashReportListener />
             <MainLayout>{children}</MainLayout>
           </ThemeProvider>
         </AnalyticsProvider>
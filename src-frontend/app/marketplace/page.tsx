@@ -4,16 +4,59 @@ import { useQueryState } from "nuqs";
 import { useRouter } from "next/navigation";
 import { AppDetail } from "@/components/marketplace/app-detail";
 import { AppList } from "@/components/marketplace/app-list";
+import { InstallConfirmationDialog } from "@/components/marketplace/install-confirmation-dialog";
+import type { MarketplaceApp } from "@/lib/api/marketplace";
 import { Suspense } from "react";
 
+/** App described only by its repository, from a `syft://install` link. */
+function appFromSource(source: string): MarketplaceApp {
+  const url = new URL(source);
+  const [owner, repo] = url.pathname.split("/").filter(Boolean);
+  return {
+    id: source,
+    name: (repo ?? owner ?? url.hostname).replace(/\.git$/, ""),
+    description: "",
+    author: owner ?? url.hostname,
+    stars: 0,
+    tags: [],
+    icon: "📦",
+    installed: false,
+    repository: source,
+  };
+}
+
 function MarketplacePageContent() {
   const router = useRouter();
   const [selectedApp] = useQueryState("id");
+  const [source] = useQueryState("source");
+
+  const appList = (
+    <AppList onSelectApp={(appId) => router.push(`/marketplace?id=${appId}`)} />
+  );
+
+  if (source) {
+    // The link was validated by the desktop shell, installing still needs
+    // the user to trust the source. Confirming installs the source with
+    // `installApp` and reports the result, the dialog stays open on failure
+    // and `onConfirm` only runs once the app is installed.
+    return (
+      <>
+        {appList}
+        <InstallConfirmationDialog
+          isOpen
+          onOpenChange={(open) => !open && router.replace("/marketplace")}
+          app={appFromSource(source)}
+          onConfirm={() => router.replace("/apps")}
+          onCancel={() => router.replace("/marketplace")}
+        />
+      </>
+    );
+  }
 
   return selectedApp ? (
     <AppDetail appId={selectedApp} onBack={() => router.push("/marketplace")} />
   ) : (
-    <AppList onSelectApp={(appId) => router.push(`/marketplace?id=${appId}`)} />
+    appList
   );
 }
 
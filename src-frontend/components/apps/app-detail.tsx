@@ -91,8 +91,14 @@ export function AppDetail({ appId, onBack }: AppDetailProps) {
   // Update the appUrl state when the app is loaded
   useEffect(() => {
     if (daemonUrl && app && app.ports.length > 0) {
-      const { protocol, hostname } = new URL(daemonUrl);
       const port = app.ports[0];
+      // The desktop app reaches the daemon through its proxy scheme, apps
+      // are still served on a local port
+      if (window.__TAURI__) {
+        setAppUrl(`http://localhost:${port}`);
+        return;
+      }
+      const { protocol, hostname } = new URL(daemonUrl);
       setAppUrl(`${protocol}//${hostname}:${port}`);
     } else {
       setAppUrl("");
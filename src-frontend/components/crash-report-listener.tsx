@@ -0,0 +1,52 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import {
+  BugReportDialog,
+  type CrashReport,
+} from "@/components/diagnostic/bug-report-dialog";
+
+/** Opens the bug report dialog when the user chose to report a crash from the previous run. */
+export function CrashReportListener() {
+  const [crashReport, setCrashReport] = useState<CrashReport>();
+
+  useEffect(() => {
+    if (typeof window === "undefined" || !window.__TAURI__) return;
+    if (window.__TAURI__.window.getCurrentWindow().label !== "main") return;
+
+    const takeCrashReport = async () => {
+      try {
+        const report =
+          await window.__TAURI__.core.invoke<CrashReport | null>(
+            "take_crash_report",
+          );
+        if (report) setCrashReport(report);
+      } catch (error) {
+        console.error("Failed to get crash report:", error);
+      }
+    };
+
+    // The report may have been offered before this window was listening
+    takeCrashReport();
+    const unlisten = window.__TAURI__.event.listen(
+      "crash-report",
+      takeCrashReport,
+    );
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  if (!crashReport) return null;
+
+  return (
+    <BugReportDialog
+      open
+      onOpenChange={(open) => {
+        if (!open) setCrashReport(undefined);
+      }}
+      crashReport={crashReport}
+    />
+  );
+}
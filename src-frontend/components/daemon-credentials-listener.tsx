@@ -0,0 +1,28 @@
+"use client";
+
+import { useEffect } from "react";
+import { useConnectionStore } from "@/stores";
+
+/** Reconnects through the proxy when the desktop app rotates the daemon token. */
+export function DaemonCredentialsListener() {
+  const { updateSettings, connect } = useConnectionStore();
+
+  useEffect(() => {
+    if (typeof window === "undefined" || !window.__TAURI__) return;
+    if (window.__TAURI__.window.getCurrentWindow().label !== "main") return;
+
+    const unlisten = window.__TAURI__.event.listen<{ url: string }>(
+      "daemon-credentials-changed",
+      async (event) => {
+        updateSettings({ url: event.payload.url, token: "" });
+        await connect();
+      },
+    );
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [updateSettings, connect]);
+
+  return null; // This component doesn't render anything
+}
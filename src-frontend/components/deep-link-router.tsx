@@ -3,21 +3,24 @@
 import { useEffect } from "react";
 import { useRouter } from "next/navigation";
 import { toast } from "@/hooks/use-toast";
-import { parseDeepLink } from "@/lib/deep-links/parser";
+import {
+  fromShellRoute,
+  type ShellDeepLinkRoute,
+} from "@/lib/deep-links/parser";
 import { createDeepLinkRouter } from "@/lib/deep-links/router";
 import { createWorkspaceHandler } from "@/lib/deep-links/handlers/workspace";
 import { createMarketplaceHandler } from "@/lib/deep-links/handlers/marketplace";
 
-const INITIAL_DEEP_LINKS_PROCESSED_KEY = "syft_initial_deep_links_processed";
-
 export function DeepLinkRouter() {
   const router = useRouter();
 
   useEffect(() => {
     const handleDeepLink = async () => {
-      if (typeof window !== "undefined" && window.__TAURI__) {
-        const { onOpenUrl, getCurrent } = window.__TAURI__.deepLink;
-
+      if (
+        typeof window !== "undefined" &&
+        window.__TAURI__ &&
+        window.__TAURI__.window.getCurrentWindow().label === "main"
+      ) {
         // Create handlers with dependencies
         const workspaceHandler = createWorkspaceHandler({ router });
         const marketplaceHandler = createMarketplaceHandler({ router });
@@ -28,20 +31,11 @@ export function DeepLinkRouter() {
           marketplaceHandler,
         });
 
-        const processUrls = async (urls: string[]) => {
-          for (const url of urls) {
+        // Links are validated by the desktop shell before they get here
+        const processRoutes = async (routes: ShellDeepLinkRoute[]) => {
+          for (const route of routes) {
             try {
-              const route = parseDeepLink(url);
-
-              if (route) {
-                await deepLinkRouter(route);
-              } else {
-                toast({
-                  title: "Invalid deep link",
-                  description: `Unable to handle link: ${url}`,
-                  variant: "destructive",
-                });
-              }
+              await deepLinkRouter(fromShellRoute(route));
             } catch (error) {
               console.error("Deep link handling failed:", error);
               toast({
@@ -56,30 +50,22 @@ export function DeepLinkRouter() {
           }
         };
 
-        // Check for initial URLs that launched the app (run once per session)
-        const hasProcessed = sessionStorage.getItem(
-          INITIAL_DEEP_LINKS_PROCESSED_KEY,
+        // Listen for new deep links while app is running
+        await window.__TAURI__.event.listen<ShellDeepLinkRoute>(
+          "deep-link",
+          (event) => processRoutes([event.payload]),
         );
-        if (!hasProcessed) {
-          sessionStorage.setItem(INITIAL_DEEP_LINKS_PROCESSED_KEY, "true");
-          try {
-            const initialUrls = await getCurrent();
-            if (initialUrls && initialUrls.length > 0) {
-              // Add a delay to ensure router and app are ready
-              setTimeout(async () => {
-                await processUrls(initialUrls);
-              }, 1000);
-            }
-          } catch (error) {
-            console.info(
-              "No initial deeplink URLs or failed to get them:",
-              error,
-            );
-          }
-        }
 
-        // Listen for new deep link events while app is running
-        await onOpenUrl(processUrls);
+        // Links that arrived before this window was listening
+        const pendingRoutes = await window.__TAURI__.core.invoke<
+          ShellDeepLinkRoute[]
+        >("take_pending_deep_links");
+        if (pendingRoutes.length > 0) {
+          // Add a delay to ensure router and app are ready
+          setTimeout(async () => {
+            await processRoutes(pendingRoutes);
+          }, 1000);
+        }
       }
     };
 
@@ -16,21 +16,37 @@ import { Textarea } from "@/components/ui/textarea";
 import { Button } from "@/components/ui/button";
 import { Checkbox } from "@/components/ui/checkbox";
 import { toast } from "@/hooks/use-toast";
-import { downloadLogs } from "@/lib/api/logs";
+import { getBugReportLogs } from "@/lib/api/logs";
 import { submitBugReport } from "@/lib/api/bug-report";
 import { APP_VERSION } from "@/lib/version";
 
+export interface CrashReport {
+  fileName: string;
+  contents: string;
+}
+
 interface BugReportDialogProps {
-  trigger: React.ReactNode;
+  trigger?: React.ReactNode;
+  open?: boolean;
+  onOpenChange?: (open: boolean) => void;
+  /** Crash report from the previous run, sent along with the bug report. */
+  crashReport?: CrashReport;
 }
 
-export function BugReportDialog({ trigger }: BugReportDialogProps) {
+export function BugReportDialog({
+  trigger,
+  open,
+  onOpenChange,
+  crashReport,
+}: BugReportDialogProps) {
   const [title, setTitle] = useState("");
   const [description, setDescription] = useState("");
   const [shareLogs, setShareLogs] = useState(true);
   const [screenshots, setScreenshots] = useState<File[]>([]);
   const [isSubmitting, setIsSubmitting] = useState(false);
-  const [isOpen, setIsOpen] = useState(false);
+  const [uncontrolledOpen, setUncontrolledOpen] = useState(false);
+  const isOpen = open ?? uncontrolledOpen;
+  const setIsOpen = onOpenChange ?? setUncontrolledOpen;
   const fileInputRef = useRef<HTMLInputElement>(null);
 
   const handleFileChange = (e: React.ChangeEvent<HTMLInputElement>) => {
@@ -58,7 +74,7 @@ export function BugReportDialog({ trigger }: BugReportDialogProps) {
       let logsBlob: Blob | undefined;
       if (shareLogs) {
         try {
-          logsBlob = await downloadLogs();
+          logsBlob = await getBugReportLogs();
         } catch (error) {
           console.error("Failed to download logs:", error);
           toast({
@@ -83,6 +99,10 @@ export function BugReportDialog({ trigger }: BugReportDialogProps) {
         arch,
         logs: logsBlob,
         screenshots: screenshots.length > 0 ? screenshots : undefined,
+        crashReport: crashReport && {
+          fileName: crashReport.fileName,
+          contents: new Blob([crashReport.contents], { type: "text/plain" }),
+        },
       });
 
       // Success! Show toast, close the dialog and reset the form
@@ -117,14 +137,15 @@ export function BugReportDialog({ trigger }: BugReportDialogProps) {
 
   return (
     <Dialog open={isOpen} onOpenChange={setIsOpen}>
-      <DialogTrigger asChild>{trigger}</DialogTrigger>
+      {trigger && <DialogTrigger asChild>{trigger}</DialogTrigger>}
       <DialogContent className="max-h-[90vh] overflow-y-auto sm:max-w-[500px]">
         <form onSubmit={handleSubmit}>
           <DialogHeader>
             <DialogTitle>Report a Bug</DialogTitle>
             <DialogDescription>
-              Please describe the issue you&apos;re experiencing. This
-              information will help us improve our platform.
+              {crashReport
+                ? "SyftBox quit unexpectedly the last time it ran. Please describe what you were doing, the crash report will be attached."
+                : "Please describe the issue you're experiencing. This information will help us improve our platform."}
             </DialogDescription>
           </DialogHeader>
           <div className="grid gap-4 py-4">
@@ -219,6 +240,14 @@ export function BugReportDialog({ trigger }: BugReportDialogProps) {
                 </p>
               </div>
             </div>
+            {crashReport && (
+              <div className="grid grid-cols-4 items-center gap-4">
+                <Label className="text-right">Crash report</Label>
+                <span className="text-muted-foreground col-span-3 truncate text-sm">
+                  {crashReport.fileName}
+                </span>
+              </div>
+            )}
             <div className="grid grid-cols-4 items-center gap-4">
               <div></div>
               <div className="col-span-3 flex items-center space-x-2">
@@ -2,6 +2,7 @@
 
 import { useState, useEffect, useCallback } from "react";
 import { ReviewDialog } from "./review-dialog";
+import { InstallConfirmationDialog } from "./install-confirmation-dialog";
 import { ChevronLeft, Star, ExternalLink } from "lucide-react";
 import ReactMarkdown from "react-markdown";
 import { Prism as SyntaxHighlighter } from "react-syntax-highlighter";
@@ -73,6 +74,7 @@ export function AppDetail({ appId, onBack }: AppDetailProps) {
   const [isUninstalling, setIsUninstalling] = useState(false);
   const [isInstalled, setIsInstalled] = useState(false);
   const [reviewDialogOpen, setReviewDialogOpen] = useState(false);
+  const [installDialogOpen, setInstallDialogOpen] = useState(false);
   const { setBreadcrumb, clearBreadcrumb } = useBreadcrumbStore();
   const { openPath } = useOpenPath();
 
@@ -181,11 +183,11 @@ export function AppDetail({ appId, onBack }: AppDetailProps) {
 
   useEffect(() => {
     const action = searchParams.get("action");
-    if (action === "install" && app && !isInstalled && !isProcessing) {
-      // Automatically trigger installation
-      handleInstall();
+    if (action === "install" && app && !app.installed) {
+      // Links may come from anywhere, so the user confirms the install
+      setInstallDialogOpen(true);
     }
-  }, [searchParams, app, isInstalled, isProcessing, handleInstall]);
+  }, [searchParams, app]);
 
   useEffect(() => {
     setBreadcrumb(<MarketplaceBreadcrumb app={app} />);
@@ -636,6 +638,17 @@ export function AppDetail({ appId, onBack }: AppDetailProps) {
           // In a real app, we would update the reviews list here
         }}
       />
+
+      <InstallConfirmationDialog
+        isOpen={installDialogOpen}
+        onOpenChange={setInstallDialogOpen}
+        app={app}
+        onConfirm={() => {
+          setIsInstalled(true);
+          setInstallDialogOpen(false);
+        }}
+        onCancel={() => setInstallDialogOpen(false)}
+      />
     </div>
   );
 }
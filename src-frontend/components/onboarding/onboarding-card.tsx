@@ -4,7 +4,7 @@ import { ConfigureStep } from "./configure-step";
 import { EmailStep } from "./email-step";
 import { VerifyStep } from "./verify-step";
 import { useConnectionStore } from "@/stores";
-import useHashParams from "@/hooks/use-hash-params";
+import useDaemonCredentials from "@/hooks/use-daemon-credentials";
 import { FormProvider, useForm } from "react-hook-form";
 import { zodResolver } from "@hookform/resolvers/zod";
 import { z } from "zod";
@@ -30,7 +30,7 @@ export function OnboardingCard({ onComplete }: OnboardingCardProps) {
   const [step, setStep] = useState<Step>("initialize");
   const [isLoading, setIsLoading] = useState<boolean>(false);
   const { datasite, updateSettings, connect } = useConnectionStore();
-  const { host, port, token } = useHashParams();
+  const credentials = useDaemonCredentials();
 
   const configForm = useForm<ConfigFormValues>({
     resolver: zodResolver(configFormSchema),
@@ -44,11 +44,14 @@ export function OnboardingCard({ onComplete }: OnboardingCardProps) {
 
   useEffect(() => {
     const attemptConnection = async () => {
-      if (!(host && port && token)) {
+      if (credentials === undefined) {
+        return;
+      }
+      if (credentials === null) {
         setStep("connect");
         return;
       }
-      updateSettings({ url: `http://${host}:${port}`, token });
+      updateSettings(credentials);
 
       // Attempt connection
       const isDesktopBuild = window?.__TAURI__ !== undefined;
@@ -89,15 +92,7 @@ export function OnboardingCard({ onComplete }: OnboardingCardProps) {
       }
     };
     attemptConnection();
-  }, [
-    connect,
-    datasite?.status,
-    host,
-    onComplete,
-    port,
-    token,
-    updateSettings,
-  ]);
+  }, [connect, credentials, datasite?.status, onComplete, updateSettings]);
 
   const handleNext = () => {
     const nextStep =
@@ -0,0 +1,28 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import { AnnouncementBar } from "@/components/ui/announcement-bar";
+
+/** Warns that the daemon is reachable from the network while remote access is on. */
+export function RemoteAccessBanner() {
+  const [remoteAccess, setRemoteAccess] = useState(false);
+
+  useEffect(() => {
+    if (typeof window === "undefined" || !window.__TAURI__) return;
+    if (window.__TAURI__.window.getCurrentWindow().label !== "main") return;
+
+    window.__TAURI__.core
+      .invoke<{ remoteAccess: boolean }>("get_settings")
+      .then(({ remoteAccess }) => setRemoteAccess(remoteAccess))
+      .catch((error) => console.error("Failed to get settings:", error));
+  }, []);
+
+  if (!remoteAccess) return null;
+
+  return (
+    <AnnouncementBar variant="warning">
+      Remote access is enabled. The SyftBox daemon is reachable from other
+      devices on your network.
+    </AnnouncementBar>
+  );
+}
@@ -0,0 +1,44 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import useHashParams from "@/hooks/use-hash-params";
+
+export interface DaemonCredentials {
+  url: string;
+  token: string;
+}
+
+/**
+ * Daemon connection details, `undefined` while loading and `null` if none are
+ * available. The desktop app only provides the URL of its proxy, which adds
+ * the token itself, other builds read the URL and token from the hash.
+ */
+const useDaemonCredentials = () => {
+  const { host, port, token } = useHashParams();
+  const [credentials, setCredentials] = useState<
+    DaemonCredentials | null | undefined
+  >(undefined);
+
+  useEffect(() => {
+    if (typeof window === "undefined") return;
+
+    if (!window.__TAURI__) {
+      setCredentials(
+        host && port && token ? { url: `http://${host}:${port}`, token } : null,
+      );
+      return;
+    }
+
+    window.__TAURI__.core
+      .invoke<{ url: string }>("get_daemon_credentials")
+      .then(({ url }) => setCredentials({ url, token: "" }))
+      .catch((error) => {
+        console.error("Failed to get daemon credentials:", error);
+        setCredentials(null);
+      });
+  }, [host, port, token]);
+
+  return credentials;
+};
+
+export default useDaemonCredentials;
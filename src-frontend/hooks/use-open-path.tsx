@@ -1,4 +1,6 @@
 import { useMemo } from "react";
+import { toast } from "@/hooks/use-toast";
+import { appErrorMessage, isAppError } from "@/lib/utils/app-error";
 
 export function useOpenPath() {
   const openPath = useMemo(() => {
@@ -6,7 +8,18 @@ export function useOpenPath() {
       typeof window !== "undefined" &&
       typeof window.__TAURI__ !== "undefined"
     ) {
-      return window.__TAURI__.opener.openPath;
+      // The desktop app checks the path and asks before opening anything risky
+      return (path: string) =>
+        window
+          .__TAURI__!.core.invoke<void>("open_path", { path })
+          .catch((error) => {
+            if (isAppError(error) && error.code === "CANCELLED") return;
+            toast({
+              title: "Could not open",
+              description: appErrorMessage(error),
+              variant: "destructive",
+            });
+          });
     }
     return (path: string) => window.open(path, "_blank");
   }, []);
@@ -8,6 +8,7 @@ export interface BugReportRequest {
   arch: string;
   logs?: Blob;
   screenshots?: File[];
+  crashReport?: { fileName: string; contents: Blob };
 }
 
 export interface BugReportResponse {
@@ -24,6 +25,15 @@ export interface BugReportResponse {
 export async function submitBugReport(
   request: BugReportRequest,
 ): Promise<BugReportResponse> {
+  if (typeof window !== "undefined" && window.__TAURI__) {
+    const { settings } = await window.__TAURI__.core.invoke<{
+      settings: { bugReportUpload: boolean };
+    }>("get_settings");
+    if (!settings.bugReportUpload) {
+      throw new Error("Bug report uploads are disabled by your organization");
+    }
+  }
+
   const { datasite } = useConnectionStore.getState();
 
   const email = datasite?.email || "";
@@ -43,6 +53,15 @@ export async function submitBugReport(
     formData.append("logs", request.logs, "syftbox-logs.zip");
   }
 
+  // Append the crash report from the previous run if present
+  if (request.crashReport) {
+    formData.append(
+      "crash_report",
+      request.crashReport.contents,
+      request.crashReport.fileName,
+    );
+  }
+
   // Append screenshots if present
   if (request.screenshots) {
     request.screenshots.forEach((file) => {
@@ -102,3 +102,18 @@ export async function downloadLogs(): Promise<Blob> {
 
   return response.blob();
 }
+
+/**
+ * Logs to attach to a bug report. In the desktop app this is the diagnostics
+ * bundle collected by the shell, which also works when the daemon is down.
+ * @returns A promise that resolves to a zip file as a Blob
+ */
+export async function getBugReportLogs(): Promise<Blob> {
+  if (typeof window !== "undefined" && window.__TAURI__) {
+    const bundle = await window.__TAURI__.core.invoke<ArrayBuffer>(
+      "get_diagnostics_bundle",
+    );
+    return new Blob([bundle], { type: "application/zip" });
+  }
+  return downloadLogs();
+}
@@ -16,19 +16,24 @@ export function createMarketplaceHandler(deps: MarketplaceHandlerDeps) {
     }
 
     const appId = route.params?.id;
+    const source = route.params?.source;
     const action = route.action;
 
-    if (!appId) {
+    let targetUrl: string;
+    if (appId) {
+      // Navigate to marketplace app detail page
+      const marketplaceUrl = `/marketplace?id=${appId}`;
+      targetUrl =
+        action === "install"
+          ? `${marketplaceUrl}&action=install`
+          : marketplaceUrl;
+    } else if (source) {
+      // Install an app from its repository
+      targetUrl = `/marketplace?action=install&source=${encodeURIComponent(source)}`;
+    } else {
       return;
     }
 
-    // Navigate to marketplace app detail page
-    const marketplaceUrl = `/marketplace?id=${appId}`;
-    const targetUrl =
-      action === "install"
-        ? `${marketplaceUrl}&action=install`
-        : marketplaceUrl;
-
     // Check if we're on the home page (initial load)
     if (window.location.pathname === "/" || window.location.pathname === "") {
       // Redirect through home page with next parameter
@@ -1,5 +1,10 @@
-export { parseDeepLink, validateDeepLink, isEmailHost } from "./parser";
+export {
+  parseDeepLink,
+  validateDeepLink,
+  isEmailHost,
+  fromShellRoute,
+} from "./parser";
 export { createDeepLinkRouter } from "./router";
 export { createWorkspaceHandler } from "./handlers/workspace";
 export { createMarketplaceHandler } from "./handlers/marketplace";
-export type { DeepLinkRoute } from "./parser";
+export type { DeepLinkRoute, ShellDeepLinkRoute } from "./parser";
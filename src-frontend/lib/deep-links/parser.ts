@@ -86,3 +86,31 @@ export function validateDeepLink(url: string): boolean {
 export function isEmailHost(host: string): boolean {
   return host.includes("@") && host.includes(".");
 }
+
+/** Routes validated by the desktop shell, see `src-tauri/src/deep_link.rs`. */
+export type ShellDeepLinkRoute =
+  | { type: "workspace"; path: string }
+  | { type: "marketplace"; appId: string; action: "view" | "install" }
+  | { type: "appInstall"; source: string }
+  | { type: "share"; email: string; path: string };
+
+export function fromShellRoute(route: ShellDeepLinkRoute): DeepLinkRoute {
+  switch (route.type) {
+    case "workspace":
+      return { type: "workspace", path: route.path };
+    case "marketplace":
+      return {
+        type: "marketplace",
+        action: route.action,
+        params: { id: route.appId },
+      };
+    case "appInstall":
+      return {
+        type: "marketplace",
+        action: "install",
+        params: { source: route.source },
+      };
+    case "share":
+      return { type: "datasite", email: route.email, path: route.path };
+  }
+}
@@ -0,0 +1,35 @@
+/** Error returned by the desktop app's commands. */
+export interface AppError {
+  code:
+    | "NOT_ALLOWED"
+    | "WINDOW_NOT_FOUND"
+    | "CANCELLED"
+    | "SETTINGS"
+    | "DAEMON"
+    | "UPDATE"
+    | "OPEN_PATH"
+    | "WINDOW"
+    | "DIAGNOSTICS"
+    | "INTERNAL";
+  message: string;
+  details: string | null;
+}
+
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    "code" in error &&
+    "message" in error
+  );
+}
+
+/** A message for the user, with the details of a command error if it has any. */
+export function appErrorMessage(error: unknown): string {
+  if (isAppError(error)) {
+    return error.details
+      ? `${error.message} ${error.details}`
+      : error.message;
+  }
+  return error instanceof Error ? error.message : String(error);
+}
@@ -33,7 +33,13 @@ export const CONNECTION_STORAGE_KEY = "connectionSettings";
 // Form schema for connection settings validation
 export const connectionFormSchema = z.object({
   url: z.string().min(1, "URL is required").url("Must be a valid URL"),
-  token: z.string().length(32, "Must be a valid token"),
+  // Empty when connecting through the desktop app's proxy, which adds the token
+  token: z
+    .string()
+    .refine(
+      (token) => token.length === 0 || token.length === 32,
+      "Must be a valid token",
+    ),
 });
 
 // Type for form values
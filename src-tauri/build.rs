@@ -1,3 +1,76 @@
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const SIDECARS: [&str; 4] = ["syftboxd", "process-wick", "uv", "uvx"];
+
+/// Hash the sidecar binaries that are about to be bundled so that the app can
+/// refuse to spawn them if they are modified after installation.
+fn generate_sidecar_hashes() {
+    println!("cargo:rerun-if-changed=target/binaries");
+    let target_triple = std::env::var("TARGET").unwrap();
+    let extension = if target_triple.contains("windows") {
+        ".exe"
+    } else {
+        ""
+    };
+
+    // Debug builds run against an external daemon and may lack sidecars, a
+    // release without them would ship without the integrity check
+    let is_release = std::env::var("PROFILE").is_ok_and(|profile| profile == "release");
+
+    let mut entries = String::new();
+    for name in SIDECARS {
+        let path = Path::new("target")
+            .join("binaries")
+            .join(format!("{}-{}{}", name, target_triple, extension));
+        match std::fs::read(&path) {
+            Ok(data) => {
+                let hash = hex::encode(Sha256::digest(&data));
+                entries.push_str(&format!("    (\"{}\", \"{}\"),\n", name, hash));
+            }
+            Err(e) if is_release => {
+                panic!("Sidecar {} is missing: {}", path.display(), e);
+            }
+            Err(_) => {}
+        }
+    }
+
+    let out_path = Path::new(&std::env::var("OUT_DIR").unwrap()).join("sidecar_hashes.rs");
+    std::fs::write(
+        out_path,
+        format!(
+            "pub const SIDECAR_HASHES: &[(&str, &str)] = &[\n{}];\n",
+            entries
+        ),
+    )
+    .unwrap();
+}
+
+/// App commands have to be granted to each window in `capabilities/`, which
+/// keeps e.g. the daemon credentials out of reach of the other windows.
+const APP_COMMANDS: &[&str] = &[
+    "update_about_window_titlebar_color",
+    "update_theme",
+    "update_window_response",
+    "get_window_state",
+    "get_diagnostics",
+    "get_settings",
+    "update_settings",
+    "set_autostart",
+    "take_pending_deep_links",
+    "get_daemon_credentials",
+    "rotate_daemon_token",
+    "open_path",
+    "take_crash_report",
+    "create_diagnostics_bundle",
+    "get_diagnostics_bundle",
+];
+
 fn main() {
-    tauri_build::build()
+    generate_sidecar_hashes();
+    tauri_build::try_build(
+        tauri_build::Attributes::new()
+            .app_manifest(tauri_build::AppManifest::new().commands(APP_COMMANDS)),
+    )
+    .expect("failed to run tauri-build")
 }
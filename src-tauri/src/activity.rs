@@ -0,0 +1,291 @@
+//! Recent sync activity reported by the daemon
+//!
+//! The daemon streams events from `GET /v1/events` as server-sent events,
+//! one JSON object per `data:` line, e.g.
+//! `data: {"type":"file_received","path":"alice@openmined.org/public/a.csv"}`.
+//! Events of other types are ignored. Daemons without the stream answer with
+//! 404, in which case it is retried rarely.
+
+use crate::error::SafeLock;
+use crate::notifications::{NotificationCategory, NotificationTarget};
+use http_body_util::{BodyExt, Full};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::http::{Method, StatusCode};
+use tauri::{AppHandle, Manager};
+
+pub const ACTIVITY_MENU_ID_PREFIX: &str = "activity_";
+
+const MAX_RECENT_ACTIVITY: usize = 10;
+const MAX_LABEL_LENGTH: usize = 40;
+/// Bursts of events, e.g. a large folder syncing, rebuild the tray menu once.
+const MENU_REBUILD_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Reconnect delay reached by doubling while the daemon has no event stream.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(10 * 60);
+
+enum ActivityStreamError {
+    /// The daemon does not provide the event stream.
+    NotFound,
+    Failed(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    FileReceived,
+    FileSent,
+    Conflict,
+    AppJobFinished,
+    ShareReceived,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ActivityEvent {
+    /// Assigned when the event is recorded, so that tray menu items keep
+    /// pointing at their event when newer ones push it down the list.
+    #[serde(skip)]
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub kind: ActivityKind,
+    /// Path relative to the workspace directory
+    pub path: String,
+}
+
+impl ActivityEvent {
+    pub fn label(&self) -> String {
+        let name = self.path.rsplit('/').next().unwrap_or(&self.path);
+        let name = if name.chars().count() > MAX_LABEL_LENGTH {
+            format!(
+                "{}...",
+                name.chars().take(MAX_LABEL_LENGTH).collect::<String>()
+            )
+        } else {
+            name.to_string()
+        };
+        match self.kind {
+            ActivityKind::FileReceived => format!("Received {}", name),
+            ActivityKind::FileSent => format!("Sent {}", name),
+            ActivityKind::Conflict => format!("Conflict in {}", name),
+            ActivityKind::AppJobFinished => format!("Job finished: {}", name),
+            ActivityKind::ShareReceived => format!("Shared with you: {}", name),
+        }
+    }
+
+    pub fn menu_id(&self) -> String {
+        format!("{}{}", ACTIVITY_MENU_ID_PREFIX, self.id)
+    }
+
+    /// Deep link that opens the item in the main window.
+    pub fn deep_link(&self) -> String {
+        format!(
+            "syft://workspace/{}",
+            self.path
+                .split('/')
+                .map(|segment| urlencoding::encode(segment).into_owned())
+                .collect::<Vec<_>>()
+                .join("/")
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct RecentActivityState {
+    pub events: Mutex<VecDeque<ActivityEvent>>,
+    next_id: AtomicU64,
+    is_menu_rebuild_pending: AtomicBool,
+}
+
+fn _notify_activity(app: &AppHandle, event: &ActivityEvent) {
+    let (category, title) = match event.kind {
+        ActivityKind::Conflict => (NotificationCategory::SyncConflict, "Sync conflict"),
+        ActivityKind::ShareReceived => (NotificationCategory::IncomingShare, "New share"),
+        _ => return,
+    };
+    crate::notifications::_notify(
+        app,
+        category,
+        title,
+        &event.label(),
+        NotificationTarget::DeepLink(event.deep_link()),
+    );
+}
+
+fn _record_activity(app: &AppHandle, mut event: ActivityEvent) {
+    log::debug!("Daemon activity: {:?}", event);
+    _notify_activity(app, &event);
+    let state = app.state::<RecentActivityState>();
+    event.id = state.next_id.fetch_add(1, Ordering::SeqCst);
+    {
+        let mut activity = state.events.safe_lock();
+        activity.push_front(event);
+        activity.truncate(MAX_RECENT_ACTIVITY);
+    }
+    if !state.is_menu_rebuild_pending.swap(true, Ordering::SeqCst) {
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(MENU_REBUILD_DELAY).await;
+            app_handle
+                .state::<RecentActivityState>()
+                .is_menu_rebuild_pending
+                .store(false, Ordering::SeqCst);
+            crate::tray::_rebuild_tray_menu(&app_handle);
+        });
+    }
+}
+
+/// Opens the main window at the activity item selected from the tray.
+pub fn _open_activity(app: &AppHandle, menu_id: &str) {
+    let Some(id) = menu_id
+        .strip_prefix(ACTIVITY_MENU_ID_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+    else {
+        return;
+    };
+    let Some(event) = app
+        .state::<RecentActivityState>()
+        .events
+        .safe_lock()
+        .iter()
+        .find(|event| event.id == id)
+        .cloned()
+    else {
+        log::warn!("Activity item {} no longer exists", id);
+        return;
+    };
+
+    crate::deep_link::_open_deep_link(app, &event.deep_link());
+}
+
+/// Subscribes to the daemon's server-sent event stream and records sync
+/// activity, reconnecting whenever the stream ends.
+pub fn _start_activity_subscription(app: &AppHandle) {
+    log::info!("Subscribing to daemon activity events");
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut delay = RECONNECT_DELAY;
+        loop {
+            match _stream_activity(&app_handle).await {
+                Ok(()) => {
+                    log::debug!("Daemon activity stream ended");
+                    delay = RECONNECT_DELAY;
+                }
+                Err(ActivityStreamError::NotFound) => {
+                    if delay == RECONNECT_DELAY {
+                        log::warn!(
+                            "The daemon has no activity event stream, recent activity and sync notifications are unavailable"
+                        );
+                    }
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+                Err(ActivityStreamError::Failed(e)) => {
+                    log::debug!("Daemon activity stream failed: {}", e);
+                }
+            }
+            tokio::time::sleep(delay).await;
+        }
+    });
+}
+
+/// Removes the complete lines from the buffer. Lines are split on raw bytes,
+/// as a multi-byte character may be split across chunks.
+fn _take_lines(buffer: &mut Vec<u8>) -> Vec<String> {
+    let mut lines = Vec::new();
+    while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+        let line_bytes: Vec<u8> = buffer.drain(..=end).collect();
+        lines.push(
+            String::from_utf8_lossy(&line_bytes)
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+        );
+    }
+    lines
+}
+
+/// Parses an event stream line, `None` for other lines and other events.
+fn _parse_activity_line(line: &str) -> Option<ActivityEvent> {
+    let data = line.strip_prefix("data:")?;
+    serde_json::from_str(data.trim()).ok()
+}
+
+async fn _stream_activity(app: &AppHandle) -> Result<(), ActivityStreamError> {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    let request = crate::daemon_client::_daemon_request(&connection, Method::GET, "/v1/events")
+        .header("Accept", "text/event-stream")
+        .body(Full::default())
+        .map_err(|e| ActivityStreamError::Failed(e.to_string()))?;
+    let response = crate::daemon_client::_send_daemon_request(&connection, request)
+        .await
+        .map_err(ActivityStreamError::Failed)?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(ActivityStreamError::NotFound);
+    }
+    if !response.status().is_success() {
+        return Err(ActivityStreamError::Failed(format!(
+            "daemon returned {}",
+            response.status()
+        )));
+    }
+
+    let mut body = response.into_body();
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|e| ActivityStreamError::Failed(e.to_string()))?;
+        let Ok(chunk) = frame.into_data() else {
+            continue;
+        };
+        buffer.extend_from_slice(&chunk);
+        for line in _take_lines(&mut buffer) {
+            if let Some(event) = _parse_activity_line(&line) {
+                _record_activity(app, event);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_activity_events() {
+        let event = _parse_activity_line(
+            r#"data: {"type":"file_received","path":"alice@openmined.org/public/a.csv"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.kind, ActivityKind::FileReceived);
+        assert_eq!(event.path, "alice@openmined.org/public/a.csv");
+        assert_eq!(event.label(), "Received a.csv");
+        assert_eq!(
+            event.deep_link(),
+            "syft://workspace/alice%40openmined.org/public/a.csv"
+        );
+    }
+
+    #[test]
+    fn ignores_other_lines_and_events() {
+        assert!(_parse_activity_line("event: message").is_none());
+        assert!(_parse_activity_line(": keep-alive").is_none());
+        assert!(_parse_activity_line(r#"data: {"type":"heartbeat"}"#).is_none());
+        assert!(_parse_activity_line("data: not json").is_none());
+    }
+
+    #[test]
+    fn keeps_characters_split_across_chunks() {
+        let line = "data: {\"type\":\"file_sent\",\"path\":\"r\u{e9}sum\u{e9}.pdf\"}\r\n";
+        let (first, second) = line.as_bytes().split_at(line.find('\u{e9}').unwrap() + 1);
+        let mut buffer = first.to_vec();
+        assert!(_take_lines(&mut buffer).is_empty());
+        buffer.extend_from_slice(second);
+        let lines = _take_lines(&mut buffer);
+        assert_eq!(lines.len(), 1);
+        assert!(buffer.is_empty());
+        let event = _parse_activity_line(&lines[0]).unwrap();
+        assert_eq!(event.kind, ActivityKind::FileSent);
+        assert_eq!(event.path, "r\u{e9}sum\u{e9}.pdf");
+    }
+}
@@ -0,0 +1,309 @@
+//! Command-line arguments of the desktop binary
+
+use crate::version::{
+    DAEMON_BUILD, DAEMON_HASH, DAEMON_VERSION, DESKTOP_BUILD, DESKTOP_HASH, DESKTOP_VERSION,
+    SIDECAR_HASHES,
+};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::io::BufRead;
+use std::path::PathBuf;
+use tauri::Url;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn level_filter(&self) -> log::LevelFilter {
+        match self {
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum CliCommand {
+    /// Open a file or folder in SyftBox
+    Open { path: PathBuf },
+}
+
+#[derive(Clone, Debug, Default, Parser)]
+#[command(name = "SyftBox", about = "The internet for private data")]
+#[command(disable_version_flag = true)]
+pub struct CliArgs {
+    /// Start with only the tray and daemon, without opening the main window
+    #[arg(long, visible_alias = "minimized")]
+    pub hidden: bool,
+
+    /// Run only the tray and daemon without ever opening a window, for
+    /// unattended datasites
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Use a separate SyftBox configuration with this name
+    #[arg(long, value_name = "NAME", value_parser = _parse_profile)]
+    pub profile: Option<String>,
+
+    /// Connect to an already running daemon instead of starting one, with
+    /// its token in $SYFTBOX_DAEMON_TOKEN or given with --daemon-token-stdin
+    #[arg(long, value_name = "URL", value_parser = _parse_daemon_url)]
+    pub daemon_url: Option<Url>,
+
+    /// Read the token of the daemon given with --daemon-url from stdin
+    #[arg(long, requires = "daemon_url")]
+    pub daemon_token_stdin: bool,
+
+    /// Never taken from the arguments, where other users could see it in the
+    /// process list.
+    #[arg(skip)]
+    pub daemon_token: Option<String>,
+
+    /// Minimum level of messages written to the log
+    #[arg(long, value_name = "LEVEL")]
+    pub log_level: Option<LogLevel>,
+
+    /// Do not check for updates in this session
+    #[arg(long)]
+    pub no_update_check: bool,
+
+    /// Restore the default settings before starting
+    #[arg(long)]
+    pub reset_settings: bool,
+
+    /// Print the desktop and daemon versions and exit
+    #[arg(short = 'V', long)]
+    pub version: bool,
+
+    /// Deep links passed by the OS when a syft:// link is opened
+    #[arg(hide = true, value_name = "URL")]
+    pub deep_links: Vec<String>,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+impl CliArgs {
+    /// Host, port and token of the external daemon given on the command line.
+    pub fn external_daemon(&self) -> Option<(String, String, String)> {
+        let url = self.daemon_url.as_ref()?;
+        Some((
+            url.host_str()?.to_string(),
+            url.port_or_known_default()?.to_string(),
+            self.daemon_token.clone()?,
+        ))
+    }
+}
+
+/// Daemon config file of a profile created with `--profile`.
+pub fn profile_config_path(profile: &str) -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".syftbox")
+            .join("profiles")
+            .join(profile)
+            .join("config.json"),
+    )
+}
+
+fn _parse_profile(name: &str) -> Result<String, String> {
+    let is_valid = !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if is_valid {
+        Ok(name.to_string())
+    } else {
+        Err("profile names may only contain letters, digits, '-' and '_' (max 32)".to_string())
+    }
+}
+
+fn _parse_daemon_url(value: &str) -> Result<Url, String> {
+    let url = Url::parse(value).map_err(|e| e.to_string())?;
+    if url.scheme() != "http" {
+        return Err("only http:// daemon URLs are supported".to_string());
+    }
+    if url.host_str().is_none() || url.port_or_known_default().is_none() {
+        return Err("the daemon URL needs a host and port".to_string());
+    }
+    Ok(url)
+}
+
+/// Environment variable holding the token of the daemon given with
+/// `--daemon-url`.
+pub const DAEMON_TOKEN_ENV: &str = "SYFTBOX_DAEMON_TOKEN";
+
+/// Removes the token from the environment once read, so that it is not passed
+/// on to the sidecars.
+fn _read_daemon_token(from_stdin: bool) -> Option<String> {
+    let token = if from_stdin {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line).ok()?;
+        line
+    } else {
+        let token = std::env::var(DAEMON_TOKEN_ENV).ok()?;
+        std::env::remove_var(DAEMON_TOKEN_ENV);
+        token
+    };
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Release builds on Windows are GUI apps without a console, so output is only
+/// visible once attached to the console of the terminal the app was started from.
+fn _attach_parent_console() {
+    #[cfg(all(windows, not(debug_assertions)))]
+    unsafe {
+        use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+fn _exit_with_error(error: clap::Error) -> ! {
+    _attach_parent_console();
+    error.exit()
+}
+
+fn _version_text() -> String {
+    let mut text = format!(
+        "SyftBox {} ({}, built {})\nsyftboxd {} ({}, built {})\n",
+        DESKTOP_VERSION, DESKTOP_HASH, DESKTOP_BUILD, DAEMON_VERSION, DAEMON_HASH, DAEMON_BUILD
+    );
+    for (name, hash) in SIDECAR_HASHES {
+        text.push_str(&format!("{}: sha256 {}\n", name, hash));
+    }
+    text
+}
+
+/// Parses arguments the way the binary was invoked. macOS may pass a
+/// `-psn_*` process serial number on launch, which is not ours to handle.
+pub fn parse_from<I, T>(args: I) -> Result<CliArgs, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    CliArgs::try_parse_from(
+        args.into_iter()
+            .map(Into::into)
+            .filter(|arg| !arg.to_string_lossy().starts_with("-psn_")),
+    )
+}
+
+/// Parses the process arguments before the app starts, printing usage errors
+/// or the version and exiting if needed.
+pub fn parse_args() -> CliArgs {
+    let mut args = parse_from(std::env::args_os()).unwrap_or_else(|e| _exit_with_error(e));
+    if args.version {
+        _attach_parent_console();
+        print!("{}", _version_text());
+        std::process::exit(0);
+    }
+    // Checked here as a second instance exits right after forwarding its
+    // arguments, so this is the only place a usage error can be reported
+    if let Some(CliCommand::Open { path }) = &args.command {
+        if !path.exists() {
+            _exit_with_error(CliArgs::command().error(
+                ErrorKind::ValueValidation,
+                format!("{} does not exist", path.display()),
+            ));
+        }
+    }
+    if args.daemon_url.is_some() {
+        args.daemon_token = _read_daemon_token(args.daemon_token_stdin);
+        if args.daemon_token.is_none() {
+            _exit_with_error(CliArgs::command().error(
+                ErrorKind::MissingRequiredArgument,
+                format!(
+                    "--daemon-url needs the daemon token in ${} or on stdin with --daemon-token-stdin",
+                    DAEMON_TOKEN_ENV
+                ),
+            ));
+        }
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _parse(args: &[&str]) -> Result<CliArgs, clap::Error> {
+        parse_from(std::iter::once("syftbox").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn parses_flags() {
+        let args = _parse(&["--minimized", "--log-level", "debug", "--no-update-check"]).unwrap();
+        assert!(args.hidden);
+        assert!(args.no_update_check);
+        assert!(matches!(args.log_level, Some(LogLevel::Debug)));
+        assert!(!args.headless);
+        assert!(_parse(&["--log-level", "loud"]).is_err());
+        assert!(_parse(&["--unknown"]).is_err());
+    }
+
+    #[test]
+    fn ignores_macos_process_serial_numbers() {
+        let args = _parse(&["-psn_0_12345", "syft://workspace/a"]).unwrap();
+        assert_eq!(args.deep_links, ["syft://workspace/a"]);
+    }
+
+    #[test]
+    fn parses_the_open_command() {
+        let args = _parse(&["open", "/tmp/data.csv"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CliCommand::Open { path }) if path == PathBuf::from("/tmp/data.csv")
+        ));
+    }
+
+    #[test]
+    fn validates_profile_names() {
+        assert_eq!(
+            _parse(&["--profile", "work_2-b"])
+                .unwrap()
+                .profile
+                .as_deref(),
+            Some("work_2-b")
+        );
+        for name in ["", "../work", "my profile", &"a".repeat(33)] {
+            assert!(_parse_profile(name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn validates_daemon_urls() {
+        let url = _parse_daemon_url("http://127.0.0.1:7938").unwrap();
+        assert_eq!(url.port(), Some(7938));
+        assert!(_parse_daemon_url("http://localhost").is_ok());
+        assert!(_parse_daemon_url("https://127.0.0.1:7938").is_err());
+        assert!(_parse_daemon_url("unix:///tmp/daemon.sock").is_err());
+        assert!(_parse_daemon_url("127.0.0.1:7938").is_err());
+        assert!(_parse(&["--daemon-token-stdin"]).is_err());
+    }
+
+    #[test]
+    fn external_daemon_needs_a_token() {
+        let mut args = _parse(&["--daemon-url", "http://10.0.0.2:8080"]).unwrap();
+        assert_eq!(args.external_daemon(), None);
+        args.daemon_token = Some("secret".to_string());
+        assert_eq!(
+            args.external_daemon(),
+            Some((
+                "10.0.0.2".to_string(),
+                "8080".to_string(),
+                "secret".to_string()
+            ))
+        );
+    }
+}
@@ -1,9 +1,17 @@
 //! Tauri command handlers
 
-use crate::state::{AppState, PendingUpdate, UpdateWindowState, UpdateWindowType};
+use crate::crash::CrashReport;
+use crate::deep_link::DeepLinkRoute;
+use crate::diagnostics::{_collect_diagnostics, Diagnostics};
+use crate::error::{AppError, SafeLock};
+use crate::settings::{Settings, SettingsState};
+use crate::state::{
+    AppState, DaemonCredentials, PendingUpdate, UpdateWindowState, UpdateWindowType,
+};
 use crate::windows::_show_update_window;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager, Theme};
+use tauri::ipc::Response;
+use tauri::{AppHandle, Manager, Theme, WebviewWindow};
 
 #[cfg(target_os = "macos")]
 use {
@@ -12,7 +20,7 @@ use {
 };
 
 #[tauri::command]
-pub fn update_theme(app: AppHandle, is_dark: bool) {
+pub fn update_theme(app: AppHandle, is_dark: bool) -> Result<(), AppError> {
     for (_, window) in app.webview_windows() {
         if let Err(e) = window.set_theme(if is_dark {
             Some(Theme::Dark)
@@ -24,56 +32,57 @@ pub fn update_theme(app: AppHandle, is_dark: bool) {
     }
     // set traffic lights inset as they get reset when the theme changes
     #[cfg(target_os = "macos")]
-    {
-        let window = app.get_webview_window("main").unwrap();
+    if let Some(window) = app.get_webview_window("main") {
         window
             .set_traffic_lights_inset(MACOS_TRAFFIC_LIGHTS_INSET_X, MACOS_TRAFFIC_LIGHTS_INSET_Y)
-            .unwrap();
+            .map_err(|e| AppError::Window(e.to_string()))?;
     }
+    Ok(())
 }
 
 #[tauri::command]
-pub fn update_about_window_titlebar_color(app: AppHandle, r: f64, g: f64, b: f64) {
-    if let Some(_about_window) = app.get_webview_window("about") {
-        log::debug!(
-            "Updating about window titlebar color: r: {}, g: {}, b: {}",
-            r,
-            g,
-            b
-        );
-
-        #[cfg(target_os = "macos")]
-        {
-            use cocoa::appkit::{NSColor, NSWindow};
-            use cocoa::base::{id, nil};
-
-            let ns_window = _about_window.ns_window().unwrap() as id;
-            unsafe {
-                let bg_color = NSColor::colorWithRed_green_blue_alpha_(
-                    nil,
-                    r / 255.0,
-                    g / 255.0,
-                    b / 255.0,
-                    1.0,
-                );
-                ns_window.setBackgroundColor_(bg_color);
-                log::debug!("Updated macOS window background and titlebar color");
-            }
-        }
-    } else {
+pub fn update_about_window_titlebar_color(
+    app: AppHandle,
+    r: f64,
+    g: f64,
+    b: f64,
+) -> Result<(), AppError> {
+    let Some(_about_window) = app.get_webview_window("about") else {
         log::warn!("About window not found when attempting to update titlebar color.");
+        return Err(AppError::WindowNotFound("about".to_string()));
+    };
+    log::debug!(
+        "Updating about window titlebar color: r: {}, g: {}, b: {}",
+        r,
+        g,
+        b
+    );
+
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::{NSColor, NSWindow};
+        use cocoa::base::{id, nil};
+
+        let ns_window = _about_window.ns_window()? as id;
+        unsafe {
+            let bg_color =
+                NSColor::colorWithRed_green_blue_alpha_(nil, r / 255.0, g / 255.0, b / 255.0, 1.0);
+            ns_window.setBackgroundColor_(bg_color);
+            log::debug!("Updated macOS window background and titlebar color");
+        }
     }
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn update_window_response(app: AppHandle, install_update: bool) -> Result<(), String> {
+pub async fn update_window_response(app: AppHandle, install_update: bool) -> Result<(), AppError> {
     log::info!(
         "Update window response received - install: {}",
         install_update
     );
     let pending_update_state = app.state::<PendingUpdate>();
     let update_opt = {
-        let guard = pending_update_state.pending_update.lock().unwrap();
+        let guard = pending_update_state.pending_update.safe_lock();
         guard.clone()
     };
 
@@ -136,6 +145,7 @@ pub async fn update_window_response(app: AppHandle, install_update: bool) -> Res
                     error_message,
                     0,
                 );
+                return Err(AppError::Update(e.to_string()));
             } else {
                 log::info!("Update installation complete - restarting application");
                 app.restart();
@@ -143,7 +153,7 @@ pub async fn update_window_response(app: AppHandle, install_update: bool) -> Res
         } else {
             log::info!("User declined update for version {}", update.version);
             let app_state_data = app.state::<Mutex<AppState>>();
-            let mut guard = app_state_data.lock().unwrap();
+            let mut guard = app_state_data.safe_lock();
             guard.prevent_auto_update_check_for_version = update.version.clone();
         }
     }
@@ -151,19 +161,139 @@ pub async fn update_window_response(app: AppHandle, install_update: bool) -> Res
 }
 
 #[tauri::command]
-pub fn get_window_state(app: AppHandle) -> UpdateWindowState {
+pub fn get_window_state(app: AppHandle) -> Result<UpdateWindowState, AppError> {
     let pending_update_state = app.state::<PendingUpdate>();
-    let guard = pending_update_state
-        .pending_update_window_state
-        .lock()
-        .unwrap();
+    let guard = pending_update_state.pending_update_window_state.safe_lock();
 
-    guard.clone().unwrap_or(UpdateWindowState {
+    Ok(guard.clone().unwrap_or(UpdateWindowState {
         update_window_type: UpdateWindowType::Checking,
         version: "".to_string(),
         current_version: app.package_info().version.to_string(),
         release_notes: "".to_string(),
         error: "".to_string(),
         progress: 0,
+    }))
+}
+
+/// Hashes the sidecar binaries, off the main thread.
+#[tauri::command]
+pub async fn get_diagnostics() -> Result<Diagnostics, AppError> {
+    tauri::async_runtime::spawn_blocking(_collect_diagnostics)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Result<SettingsState, AppError> {
+    Ok(crate::settings::_get_settings_state(&app))
+}
+
+#[tauri::command]
+pub fn update_settings(app: AppHandle, settings: Settings) -> Result<SettingsState, AppError> {
+    log::info!("Updating settings");
+    crate::settings::_update_settings(&app, settings).map_err(AppError::Settings)?;
+    Ok(crate::settings::_get_settings_state(&app))
+}
+
+#[tauri::command]
+pub fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    log::info!("Setting autostart to {}", enabled);
+    crate::settings::_set_autostart(&app, enabled).map_err(AppError::Settings)
+}
+
+#[tauri::command]
+pub fn take_pending_deep_links(app: AppHandle) -> Result<Vec<DeepLinkRoute>, AppError> {
+    Ok(crate::deep_link::_take_pending_deep_links(&app))
+}
+
+/// Only allowed for the main window, see `capabilities/main.json`.
+#[tauri::command]
+pub fn get_daemon_credentials(
+    app: AppHandle,
+    window: WebviewWindow,
+) -> Result<DaemonCredentials, AppError> {
+    if window.label() != "main" {
+        return Err(AppError::NotAllowed);
+    }
+    Ok(crate::daemon_client::_get_daemon_connection(&app).credentials())
+}
+
+/// Replaces the opener plugin in all windows, see [`crate::open_path`].
+#[tauri::command]
+pub async fn open_path(
+    app: AppHandle,
+    window: WebviewWindow,
+    path: String,
+) -> Result<(), AppError> {
+    crate::open_path::_open_path(&app, window.label(), &path).await
+}
+
+#[tauri::command]
+pub async fn rotate_daemon_token(
+    app: AppHandle,
+    window: WebviewWindow,
+) -> Result<DaemonCredentials, AppError> {
+    if window.label() != "main" {
+        return Err(AppError::NotAllowed);
+    }
+    #[cfg(not(debug_assertions))]
+    return crate::daemon::_rotate_daemon_token(&app)
+        .await
+        .map_err(AppError::Daemon);
+    #[cfg(debug_assertions)]
+    {
+        let _ = app;
+        Err(AppError::Daemon(
+            "The daemon token cannot be rotated in debug mode".to_string(),
+        ))
+    }
+}
+
+/// Crash report from the previous run the user chose to attach to a bug
+/// report, `None` once taken.
+#[tauri::command]
+pub fn take_crash_report(
+    app: AppHandle,
+    window: WebviewWindow,
+) -> Result<Option<CrashReport>, AppError> {
+    if window.label() != "main" {
+        return Err(AppError::NotAllowed);
+    }
+    Ok(crate::crash::_take_crash_report(&app))
+}
+
+/// Saves a diagnostics bundle to the Downloads folder and returns its path.
+#[tauri::command]
+pub async fn create_diagnostics_bundle(app: AppHandle) -> Result<String, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::diagnostics_bundle::_save_diagnostics_bundle(&app)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map(|path| path.to_string_lossy().into_owned())
+    .map_err(AppError::Diagnostics)
+}
+
+/// Zipped diagnostics bundle to attach to a bug report, which works without
+/// the daemon unlike its logs download.
+#[tauri::command]
+pub async fn get_diagnostics_bundle(
+    app: AppHandle,
+    window: WebviewWindow,
+) -> Result<Response, AppError> {
+    if window.label() != "main" {
+        return Err(AppError::NotAllowed);
+    }
+    if !crate::settings::_effective_settings(&app).bug_report_upload {
+        return Err(AppError::Settings(
+            "Bug report uploads are disabled".to_string(),
+        ));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::diagnostics_bundle::_build_diagnostics_bundle(&app)
     })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map(Response::new)
+    .map_err(AppError::Diagnostics)
 }
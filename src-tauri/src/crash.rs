@@ -0,0 +1,273 @@
+//! Crash files written by the panic hook and the report offered on next launch
+
+use crate::error::SafeLock;
+use crate::version::{
+    DAEMON_BUILD, DAEMON_HASH, DAEMON_VERSION, DESKTOP_BUILD, DESKTOP_HASH, DESKTOP_VERSION,
+};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+use tauri_plugin_opener::OpenerExt;
+
+/// Log lines included in a crash file.
+const CRASH_LOG_LINES: usize = 200;
+/// Suffix of crash files that were already offered to the user.
+const SEEN_SUFFIX: &str = ".seen.txt";
+
+const VIEW_BUTTON: &str = "View";
+const ATTACH_BUTTON: &str = "Report Bug";
+const DISCARD_BUTTON: &str = "Discard";
+
+/// Crash report waiting to be attached to a bug report by the main window.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub file_name: String,
+    pub contents: String,
+}
+
+#[derive(Default)]
+pub struct CrashReportState(Mutex<Option<CrashReport>>);
+
+pub fn _crashes_dir() -> PathBuf {
+    crate::utils::_logs_dir().join("crashes")
+}
+
+fn _tail_desktop_log() -> String {
+    let path = crate::utils::_logs_dir().join("SyftBoxDesktop.log");
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return format!("<{} could not be read>", path.display());
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(CRASH_LOG_LINES)..].join("\n")
+}
+
+fn _crash_report(info: &std::panic::PanicHookInfo<'_>, now: time::OffsetDateTime) -> String {
+    let thread = std::thread::current();
+    let mut report = String::new();
+    let _ = writeln!(report, "SyftBox crashed at {} UTC", now);
+    let _ = writeln!(report, "Thread: {}", thread.name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "{}", info);
+    let _ = writeln!(report);
+    let _ = writeln!(
+        report,
+        "SyftBox {} ({}, {})",
+        DESKTOP_VERSION, DESKTOP_HASH, DESKTOP_BUILD
+    );
+    let _ = writeln!(
+        report,
+        "syftboxd {} ({}, {})",
+        DAEMON_VERSION, DAEMON_HASH, DAEMON_BUILD
+    );
+    let _ = writeln!(
+        report,
+        "OS: {} ({} {})",
+        sysinfo::System::long_os_version().unwrap_or_else(|| "unknown".to_string()),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Backtrace:");
+    let _ = writeln!(report, "{}", std::backtrace::Backtrace::force_capture());
+    let _ = writeln!(report, "Last {} log lines:", CRASH_LOG_LINES);
+    let _ = writeln!(report, "{}", _tail_desktop_log());
+    report
+}
+
+fn _write_crash_file(dir: &Path, info: &std::panic::PanicHookInfo<'_>) -> std::io::Result<PathBuf> {
+    let now = time::OffsetDateTime::now_utc();
+    let path = dir.join(format!("crash-{}.txt", now.unix_timestamp()));
+    std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, _crash_report(info, now)))?;
+    Ok(path)
+}
+
+/// Writes a crash file to the directory returned by `crashes_dir` for every
+/// panic, before running the previous hook. Must not panic itself, so every
+/// failure is ignored.
+fn _set_crash_file_hook(crashes_dir: impl Fn() -> PathBuf + Send + Sync + 'static) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match _write_crash_file(&crashes_dir(), info) {
+            Ok(path) => log::error!(
+                "Panicked: {}, crash report written to {}",
+                info,
+                path.display()
+            ),
+            Err(_) => log::error!("Panicked: {}", info),
+        }
+        default_hook(info);
+    }));
+}
+
+pub fn install_panic_hook() {
+    _set_crash_file_hook(_crashes_dir);
+}
+
+/// Crash files that have not been offered to the user yet, oldest first.
+fn _pending_crash_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with("crash-")
+                        && name.ends_with(".txt")
+                        && !name.ends_with(SEEN_SUFFIX)
+                })
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn _mark_seen(path: &Path) -> PathBuf {
+    let seen = path.with_extension("").with_extension(&SEEN_SUFFIX[1..]);
+    if let Err(e) = std::fs::rename(path, &seen) {
+        log::error!(
+            "Failed to mark crash file {} as seen: {}",
+            path.display(),
+            e
+        );
+        return path.to_path_buf();
+    }
+    seen
+}
+
+fn _attach_crash_report(app: &AppHandle, path: &Path) {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            log::error!("Failed to read crash file {}: {}", path.display(), e);
+            return;
+        }
+    };
+    let report = CrashReport {
+        file_name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        contents,
+    };
+    *app.state::<CrashReportState>().0.safe_lock() = Some(report);
+    crate::utils::show_main_window(app);
+    if let Err(e) = app.emit_to("main", "crash-report", ()) {
+        log::error!("Failed to send crash report to main window: {}", e);
+    }
+}
+
+/// Handed to the main window's bug report dialog once.
+pub fn _take_crash_report(app: &AppHandle) -> Option<CrashReport> {
+    app.state::<CrashReportState>().0.safe_lock().take()
+}
+
+/// Offers the most recent crash file from a previous run to the user. Older
+/// ones are kept for the diagnostics bundle without asking again.
+pub fn _offer_crash_report(app: &AppHandle) {
+    let mut pending = _pending_crash_files(&_crashes_dir());
+    let Some(latest) = pending.pop() else {
+        return;
+    };
+    for path in pending {
+        _mark_seen(&path);
+    }
+    log::info!("Found crash report {}", latest.display());
+
+    let is_upload_locked = app
+        .state::<crate::policy::Policy>()
+        .locked_settings()
+        .iter()
+        .any(|name| name == "bugReportUpload");
+    let can_attach = !crate::headless::_is_headless(app) && !is_upload_locked;
+    let message = if can_attach {
+        "SyftBox quit unexpectedly the last time it ran. A crash report was saved, would you like to send it to us with a bug report?"
+    } else {
+        "SyftBox quit unexpectedly the last time it ran. A crash report was saved."
+    };
+    let buttons = if can_attach {
+        MessageDialogButtons::YesNoCancelCustom(
+            VIEW_BUTTON.to_string(),
+            ATTACH_BUTTON.to_string(),
+            DISCARD_BUTTON.to_string(),
+        )
+    } else {
+        MessageDialogButtons::OkCancelCustom(VIEW_BUTTON.to_string(), DISCARD_BUTTON.to_string())
+    };
+    let app_handle = app.clone();
+    app.dialog()
+        .message(message)
+        .kind(MessageDialogKind::Warning)
+        .title("SyftBox crashed")
+        .buttons(buttons)
+        .show_with_result(move |result| {
+            let choice = match &result {
+                MessageDialogResult::Custom(label) => label.as_str(),
+                MessageDialogResult::Yes | MessageDialogResult::Ok => VIEW_BUTTON,
+                MessageDialogResult::No => ATTACH_BUTTON,
+                _ => DISCARD_BUTTON,
+            };
+            match choice {
+                VIEW_BUTTON => {
+                    let seen = _mark_seen(&latest);
+                    if let Err(e) = app_handle
+                        .opener()
+                        .open_path(seen.to_string_lossy(), None::<&str>)
+                    {
+                        log::error!("Failed to open crash file {}: {}", seen.display(), e);
+                    }
+                }
+                ATTACH_BUTTON => {
+                    let seen = _mark_seen(&latest);
+                    _attach_crash_report(&app_handle, &seen);
+                }
+                _ => {
+                    log::info!("Discarding crash report {}", latest.display());
+                    if let Err(e) = std::fs::remove_file(&latest) {
+                        log::error!("Failed to remove crash file {}: {}", latest.display(), e);
+                    }
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_hook_writes_a_crash_file_offered_on_next_launch() {
+        let dir = std::env::temp_dir().join(format!("syftbox-crashes-{}", std::process::id()));
+        let hook_dir = dir.clone();
+        _set_crash_file_hook(move || hook_dir.clone());
+        let result = std::panic::catch_unwind(|| panic!("test crash"));
+        let _ = std::panic::take_hook();
+        assert!(result.is_err());
+
+        // What `_offer_crash_report` shows the dialog for on the next launch
+        let pending = _pending_crash_files(&dir);
+        assert_eq!(pending.len(), 1);
+        let name = pending[0]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        assert!(name.starts_with("crash-") && name.ends_with(".txt"));
+        let contents = std::fs::read_to_string(&pending[0]).unwrap();
+        assert!(contents.contains("test crash"));
+        assert!(contents.contains(&format!("SyftBox {}", DESKTOP_VERSION)));
+        assert!(contents.contains("Backtrace:"));
+
+        let seen = _mark_seen(&pending[0]);
+        assert!(seen.to_string_lossy().ends_with(SEEN_SUFFIX));
+        assert!(_pending_crash_files(&dir).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! Content Security Policy sent with the pages of each window
+//!
+//! Inline scripts emitted by the frontend build are allowed by their hash, so
+//! that any other inline script, e.g. injected through marketplace metadata,
+//! is blocked. Only bundled assets go through this, the dev server in debug
+//! builds serves pages without a policy.
+//!
+//! `tauri.conf.json` holds a baseline policy without any remote sources,
+//! which the policies here replace for the windows that need more.
+//!
+//! Violations are reported to the shell through the daemon proxy scheme, so
+//! that those during page load are logged too.
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use tauri::http::{header, HeaderValue, Request, Response};
+use tauri::{AppHandle, Manager};
+
+/// Tauri IPC, which uses a custom scheme on Windows and `ipc:` elsewhere.
+const IPC_SOURCES: [&str; 2] = ["ipc:", "http://ipc.localhost"];
+const ANALYTICS_SOURCE: &str = "https://us.aptabase.com";
+
+/// Dashboard widgets, loaded into `srcdoc` iframes of the main window which
+/// inherit its policy. Their inline scripts are allowed by hash as well.
+const WIDGET_PAGES: [&str; 5] = [
+    "widgets/api-broadcast.html",
+    "widgets/api-requests.html",
+    "widgets/projects-rds.html",
+    "widgets/queue-rpc.html",
+    "widgets/system-load.html",
+];
+/// Loaded by the system load widget.
+const CHART_JS_SOURCE: &str = "https://cdnjs.cloudflare.com/ajax/libs/Chart.js/3.9.1/chart.min.js";
+
+pub const CSP_REPORT_PATH: &str = "/csp-report";
+/// Reports are only logged, larger ones are cut.
+const MAX_CSP_REPORT_BYTES: usize = 8 * 1024;
+
+/// Policies of the app's windows, generated at startup from the daemon
+/// connection, and the script sources each window needs besides those of the
+/// page itself.
+pub struct WindowCsps(HashMap<&'static str, (String, Vec<String>)>);
+
+fn _policy(connect_src: &[&str], frame_src: &[&str]) -> String {
+    let connect_src = ["'self'"]
+        .iter()
+        .chain(IPC_SOURCES.iter())
+        .chain(connect_src.iter())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let frame_src = if frame_src.is_empty() {
+        "'none'".to_string()
+    } else {
+        frame_src.join(" ")
+    };
+    [
+        "default-src 'self'".to_string(),
+        "style-src 'self' 'unsafe-inline'".to_string(),
+        "img-src 'self' data: blob: https:".to_string(),
+        "font-src 'self' data:".to_string(),
+        format!("connect-src {}", connect_src),
+        format!("frame-src {}", frame_src),
+        "object-src 'none'".to_string(),
+        "base-uri 'none'".to_string(),
+        "form-action 'none'".to_string(),
+        "frame-ancestors 'none'".to_string(),
+        format!(
+            "report-uri {}{}",
+            crate::daemon_proxy::PROXY_BASE_URL,
+            CSP_REPORT_PATH
+        ),
+        "report-to csp".to_string(),
+    ]
+    .join("; ")
+}
+
+fn _widget_script_sources(app: &AppHandle) -> Vec<String> {
+    let mut sources = vec![CHART_JS_SOURCE.to_string()];
+    for page in WIDGET_PAGES {
+        match app.asset_resolver().get(page.to_string()) {
+            Some(asset) => sources.extend(_inline_script_hashes(&String::from_utf8_lossy(
+                &asset.bytes,
+            ))),
+            None => log::warn!("Widget page {} is not bundled", page),
+        }
+    }
+    sources
+}
+
+pub fn _generate_window_csps(app: &AppHandle) -> WindowCsps {
+    let daemon_url = crate::daemon_client::_get_daemon_connection(app)
+        .credentials()
+        .url;
+    let main = _policy(
+        &[
+            &daemon_url,
+            "https://syftbox.openmined.org",
+            "https://www.syftbox.net",
+            ANALYTICS_SOURCE,
+        ],
+        // Installed apps are served on local ports
+        &["http://localhost:*", "http://127.0.0.1:*"],
+    );
+    let about = _policy(&[ANALYTICS_SOURCE], &[]);
+    let updates = _policy(&[ANALYTICS_SOURCE], &[]);
+    WindowCsps(HashMap::from([
+        ("main", (main, _widget_script_sources(app))),
+        ("about", (about, Vec::new())),
+        ("updates", (updates, Vec::new())),
+    ]))
+}
+
+/// Hashes of the inline `<script>` elements of a page, in CSP source format.
+fn _inline_script_hashes(html: &str) -> Vec<String> {
+    let mut hashes = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<script") {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+        let Some(end) = rest.find("</script>") else {
+            break;
+        };
+        if !tag.contains(" src=") && end > 0 {
+            let hash = Sha256::digest(rest[..end].as_bytes());
+            hashes.push(format!(
+                "'sha256-{}'",
+                base64::engine::general_purpose::STANDARD.encode(hash)
+            ));
+        }
+        rest = &rest[end..];
+    }
+    hashes
+}
+
+/// Adds the window's policy to every page served to it, to be passed to
+/// `on_web_resource_request` when building the window.
+pub fn _csp_handler(
+    app: &AppHandle,
+    label: &str,
+) -> impl Fn(Request<Vec<u8>>, &mut Response<Cow<'static, [u8]>>) + Send + Sync + 'static {
+    let (policy, window_script_src) = app
+        .state::<WindowCsps>()
+        .0
+        .get(label)
+        .cloned()
+        .unwrap_or_else(|| {
+            log::warn!(
+                "No CSP defined for window {}, using the strictest one",
+                label
+            );
+            (_policy(&[], &[]), Vec::new())
+        });
+    let reporting_endpoints = format!(
+        "csp=\"{}{}\"",
+        crate::daemon_proxy::PROXY_BASE_URL,
+        CSP_REPORT_PATH
+    );
+    move |_request, response| {
+        let is_html = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/html"));
+        if !is_html {
+            return;
+        }
+        let script_src = ["'self'".to_string()]
+            .into_iter()
+            .chain(_inline_script_hashes(&String::from_utf8_lossy(
+                response.body(),
+            )))
+            .chain(window_script_src.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let csp = format!("{}; script-src {}", policy, script_src);
+        match (
+            HeaderValue::from_str(&csp),
+            HeaderValue::from_str(&reporting_endpoints),
+        ) {
+            (Ok(csp), Ok(reporting_endpoints)) => {
+                let headers = response.headers_mut();
+                headers.insert(header::CONTENT_SECURITY_POLICY, csp);
+                headers.insert("Reporting-Endpoints", reporting_endpoints);
+            }
+            (Err(e), _) | (_, Err(e)) => log::error!("Invalid CSP header: {}", e),
+        }
+    }
+}
+
+/// Logs a violation report sent by a webview to [`CSP_REPORT_PATH`].
+pub fn _log_csp_report(label: &str, report: &[u8]) {
+    let report = String::from_utf8_lossy(&report[..report.len().min(MAX_CSP_REPORT_BYTES)]);
+    log::error!("CSP violation in window {}: {}", label, report);
+}
@@ -0,0 +1,268 @@
+//! Supervision of the syftboxd daemon sidecar
+
+use crate::error::SafeLock;
+use crate::notifications::{NotificationCategory, NotificationTarget};
+use crate::state::DaemonCredentials;
+use crate::version::DAEMON_VERSION;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_shell::{
+    process::{Command, CommandChild, CommandEvent},
+    ShellExt,
+};
+
+#[derive(Default)]
+pub struct DaemonProcess {
+    pub child: Mutex<Option<CommandChild>>,
+    /// Version of the binary the daemon was last spawned from.
+    pub version: Mutex<String>,
+    /// Incremented whenever the daemon is stopped on purpose, so that the exit
+    /// of a superseded process is not treated as a crash.
+    pub generation: AtomicU64,
+    /// Set while a freshly swapped binary is being health checked. Exits during
+    /// this window are handled by the caller instead of quitting the app.
+    pub on_probation: AtomicBool,
+}
+
+fn _daemon_command(app: &AppHandle) -> Result<(Command, String), String> {
+    match crate::daemon_updates::_get_installed_daemon(app) {
+        Some((version, path)) => {
+            log::info!("Using updated syftboxd {} from {}", version, path.display());
+            Ok((app.shell().command(path), version))
+        }
+        None => {
+            if !crate::integrity::_is_sidecar_intact(app, "syftboxd") {
+                return Err("Refusing to spawn syftboxd: integrity check failed".to_string());
+            }
+            let command = app
+                .shell()
+                .sidecar("syftboxd")
+                .map_err(|e| format!("Failed to find the syftboxd sidecar: {}", e))?;
+            Ok((command, DAEMON_VERSION.to_string()))
+        }
+    }
+}
+
+pub fn _spawn_daemon(app: &AppHandle) -> Result<(), String> {
+    let mut connection = crate::daemon_client::_get_daemon_connection(app);
+    let (command, version) = _daemon_command(app)?;
+    log::info!("Starting syftboxd {} sidecar", version);
+
+    // A daemon update may have been rolled back to a version without socket
+    // support since the connection was set up
+    if connection.socket.is_some() && !crate::daemon_socket::_daemon_supports_socket(&version) {
+        log::info!(
+            "syftboxd {} cannot listen on a socket, using port {}",
+            version,
+            connection.port
+        );
+        connection.socket = None;
+        app.state::<Mutex<crate::state::DaemonConnection>>()
+            .safe_lock()
+            .socket = None;
+    }
+    let http_addr = match &connection.socket {
+        Some(socket) => {
+            crate::daemon_socket::_prepare_daemon_socket(socket)?;
+            crate::daemon_socket::_daemon_listen_addr(socket)
+        }
+        None => format!("{}:{}", connection.host, connection.port),
+    };
+    let mut args = vec![
+        "daemon".to_string(),
+        "--http-addr".to_string(),
+        http_addr,
+        "--http-token".to_string(),
+        connection.token.clone(),
+    ];
+    let profile = app
+        .state::<crate::state::ActiveProfile>()
+        .0
+        .safe_lock()
+        .clone();
+    if let Some(config_path) = profile.as_deref().and_then(crate::cli::profile_config_path) {
+        log::info!("Using profile config {}", config_path.display());
+        args.extend(["--config".to_string(), config_path.display().to_string()]);
+    }
+
+    let binaries_path = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .ok_or("Failed to get the directory of the app's binaries")?;
+    let (mut rx, child) = command
+        .args(args)
+        .env("SYFTBOX_DESKTOP_BINARIES_PATH", binaries_path)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn syftboxd: {}", e))?;
+
+    if let Some(socket) = connection.socket {
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if _wait_for_daemon_health(&app_handle, Duration::from_secs(60)).await {
+                crate::daemon_socket::_restrict_daemon_socket(&socket);
+            }
+        });
+    }
+
+    let daemon_process = app.state::<DaemonProcess>();
+    let generation = daemon_process.generation.load(Ordering::SeqCst);
+    *daemon_process.child.safe_lock() = Some(child);
+    *daemon_process.version.safe_lock() = version;
+    crate::utils::_retarget_process_wick(app);
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Terminated(payload) => {
+                    let exit_code = payload.code.unwrap_or_else(|| {
+                        log::warn!("syftboxd sidecar exited without a status code");
+                        1
+                    });
+                    _on_daemon_exit(&app_handle, generation, exit_code);
+                    break;
+                }
+                CommandEvent::Error(e) => {
+                    log::error!("syftboxd sidecar error: {}", e);
+                }
+                _ => {}
+            }
+        }
+    });
+    Ok(())
+}
+
+fn _on_daemon_exit(app: &AppHandle, generation: u64, exit_code: i32) {
+    let daemon_process = app.state::<DaemonProcess>();
+    if daemon_process.generation.load(Ordering::SeqCst) != generation {
+        log::info!("syftboxd sidecar stopped with code: {}", exit_code);
+        return;
+    }
+    daemon_process.child.safe_lock().take();
+    if daemon_process.on_probation.load(Ordering::SeqCst) {
+        log::warn!(
+            "syftboxd sidecar exited with code {} while being health checked",
+            exit_code
+        );
+        return;
+    }
+
+    log::error!(
+        "syftboxd sidecar exited unexpectedly with code: {}",
+        exit_code
+    );
+    crate::notifications::_notify(
+        app,
+        NotificationCategory::DaemonStatus,
+        "SyftBox stopped",
+        "The SyftBox daemon stopped unexpectedly. Please check the logs for more information.",
+        NotificationTarget::MainWindow,
+    );
+    app.dialog()
+        .message("SyftBox daemon exited unexpectedly. Please check the logs for more information.")
+        .kind(MessageDialogKind::Error)
+        .title("Error")
+        .blocking_show();
+
+    app.exit(exit_code);
+}
+
+pub fn _stop_daemon(app: &AppHandle) {
+    let daemon_process = app.state::<DaemonProcess>();
+    daemon_process.generation.fetch_add(1, Ordering::SeqCst);
+    if let Some(child) = daemon_process.child.safe_lock().take() {
+        log::info!("Stopping syftboxd sidecar");
+        if let Err(e) = child.kill() {
+            log::error!("Failed to stop syftboxd sidecar: {}", e);
+        }
+    }
+}
+
+pub async fn _restart_daemon(app: &AppHandle) -> Result<(), String> {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    if connection.external {
+        return Err("The daemon was started outside SyftBox".to_string());
+    }
+    // Restarts for other reasons must not resume sync the user paused, the
+    // daemon is started with the new configuration when sync is resumed
+    if crate::pause::_is_sync_paused(app) {
+        log::info!("Sync is paused, not restarting syftboxd");
+        _stop_daemon(app);
+        return Ok(());
+    }
+    log::info!("Restarting syftboxd sidecar");
+    _stop_daemon(app);
+    crate::tray::_set_daemon_status(app, crate::tray::DaemonStatus::Starting);
+
+    // A stale socket is removed before spawning, only a port has to be freed
+    if connection.socket.is_none() {
+        for _ in 0..10 {
+            if !crate::utils::_is_port_in_use(&connection.port) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+    _spawn_daemon(app)
+}
+
+fn _set_daemon_token(app: &AppHandle, token: String) -> DaemonCredentials {
+    let connection_state = app.state::<Mutex<crate::state::DaemonConnection>>();
+    let mut connection = connection_state.safe_lock();
+    connection.token = token;
+    connection.credentials()
+}
+
+/// Restarts the daemon with a new token and sends the new credentials to the
+/// main window.
+pub async fn _rotate_daemon_token(app: &AppHandle) -> Result<DaemonCredentials, String> {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    if connection.external {
+        return Err("The daemon was started outside SyftBox".to_string());
+    }
+    log::info!("Rotating daemon token");
+    let credentials = _set_daemon_token(app, crate::utils::_generate_secure_token());
+    let result = match _restart_daemon(app).await {
+        Ok(()) if crate::pause::_is_sync_paused(app) => Ok(()),
+        Ok(()) if _wait_for_daemon_health(app, Duration::from_secs(30)).await => Ok(()),
+        Ok(()) => Err("Daemon did not become healthy with the new token".to_string()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        // The main window and access file still hold the old token, so the
+        // daemon is brought back with it
+        log::error!(
+            "Failed to rotate daemon token, restoring the old one: {}",
+            e
+        );
+        _set_daemon_token(app, connection.token);
+        if let Err(e) = _restart_daemon(app).await {
+            log::error!("Failed to restart daemon with the old token: {}", e);
+        }
+        return Err(e);
+    }
+
+    if crate::headless::_is_headless(app) {
+        crate::headless::_write_daemon_access_file(app);
+    }
+    if let Err(e) = app.emit_to("main", "daemon-credentials-changed", credentials.clone()) {
+        log::error!("Failed to send new daemon credentials: {}", e);
+    }
+    Ok(credentials)
+}
+
+pub async fn _wait_for_daemon_health(app: &AppHandle, timeout: Duration) -> bool {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        match crate::daemon_client::_get_daemon_status(&connection).await {
+            Ok(_) => return true,
+            Err(e) => log::debug!("Daemon health check failed: {}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    false
+}
@@ -0,0 +1,110 @@
+//! Minimal HTTP client for the daemon API used by the shell
+
+use crate::error::SafeLock;
+use crate::state::DaemonConnection;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper_util::rt::TokioIo;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::http::{request, Method, Request, Response};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+pub fn _get_daemon_connection(app: &AppHandle) -> DaemonConnection {
+    app.state::<Mutex<DaemonConnection>>().safe_lock().clone()
+}
+
+/// Starts a request to the daemon with the shell's credentials.
+pub fn _daemon_request(
+    connection: &DaemonConnection,
+    method: Method,
+    path_and_query: &str,
+) -> request::Builder {
+    Request::builder()
+        .method(method)
+        .uri(path_and_query)
+        .header(
+            "Host",
+            format!("{}:{}", connection.client_host(), connection.port),
+        )
+        .header("Authorization", format!("Bearer {}", connection.token))
+}
+
+async fn _send<S>(io: S, request: Request<Full<Bytes>>) -> Result<Response<Incoming>, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(io))
+        .await
+        .map_err(|e| format!("Failed to connect to daemon: {}", e))?;
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = connection.await {
+            log::debug!("Daemon connection closed: {}", e);
+        }
+    });
+    sender
+        .send_request(request)
+        .await
+        .map_err(|e| format!("Daemon request failed: {}", e))
+}
+
+/// Sends a request over the daemon's socket, or its TCP port when it is not
+/// listening on a socket.
+pub async fn _send_daemon_request(
+    connection: &DaemonConnection,
+    request: Request<Full<Bytes>>,
+) -> Result<Response<Incoming>, String> {
+    let Some(socket) = &connection.socket else {
+        let port = connection
+            .port
+            .parse::<u16>()
+            .map_err(|e| format!("Invalid daemon port {}: {}", connection.port, e))?;
+        let stream = tokio::net::TcpStream::connect((connection.client_host(), port))
+            .await
+            .map_err(|e| format!("Failed to connect to daemon: {}", e))?;
+        return _send(stream, request).await;
+    };
+
+    #[cfg(unix)]
+    let stream = tokio::net::UnixStream::connect(socket).await;
+    #[cfg(windows)]
+    let stream = tokio::net::windows::named_pipe::ClientOptions::new().open(socket);
+    let stream = stream.map_err(|e| format!("Failed to connect to daemon socket: {}", e))?;
+    _send(stream, request).await
+}
+
+pub async fn _get_daemon_status(
+    connection: &DaemonConnection,
+) -> Result<serde_json::Value, String> {
+    let request = _daemon_request(connection, Method::GET, "/v1/status")
+        .body(Full::default())
+        .map_err(|e| e.to_string())?;
+    let body = tokio::time::timeout(Duration::from_secs(3), async {
+        let response = _send_daemon_request(connection, request).await?;
+        if !response.status().is_success() {
+            return Err(format!("daemon returned {}", response.status()));
+        }
+        response
+            .into_body()
+            .collect()
+            .await
+            .map(|body| body.to_bytes())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|_| "request timed out".to_string())
+    .and_then(|result| result)
+    .map_err(|e| format!("Failed to get daemon status: {}", e))?;
+    serde_json::from_slice(&body).map_err(|e| format!("Failed to parse daemon status: {}", e))
+}
+
+/// Returns the datasite workspace directory from the daemon config.
+pub async fn _get_workspace_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let status = _get_daemon_status(&_get_daemon_connection(app)).await?;
+    status["datasite"]["config"]["data_dir"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or_else(|| "Datasite is not configured yet".to_string())
+}
@@ -0,0 +1,263 @@
+//! `syftbox://` URI scheme that forwards webview requests to the daemon
+//!
+//! The daemon token never leaves the shell: it is added to every forwarded
+//! request after checking which webview and origin the request came from.
+
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::Bytes;
+use tauri::http::{header, HeaderName, HeaderValue, Method, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, UriSchemeContext, UriSchemeResponder, Wry};
+
+pub const PROXY_SCHEME: &str = "syftbox";
+
+/// Base URL of the proxy as seen from the webview, Windows webviews map
+/// custom schemes to `http://<scheme>.localhost`.
+#[cfg(windows)]
+pub const PROXY_BASE_URL: &str = "http://syftbox.localhost";
+#[cfg(not(windows))]
+pub const PROXY_BASE_URL: &str = "syftbox://localhost";
+
+/// Request headers passed on to the daemon. `Authorization` is always set by
+/// the shell and never taken from the webview.
+const FORWARDED_HEADERS: [header::HeaderName; 3] =
+    [header::ACCEPT, header::CONTENT_TYPE, header::RANGE];
+
+/// Responses are handed to the webview in one piece, as custom schemes cannot
+/// stream. Larger files have to be read in parts with `Range`.
+const MAX_RESPONSE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Origins the app's own pages are served from.
+const APP_ORIGINS: [&str; 3] = [
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+];
+
+pub fn _handle_proxy_request(
+    ctx: UriSchemeContext<'_, Wry>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    let label = ctx.webview_label().to_string();
+    tauri::async_runtime::spawn(async move {
+        responder.respond(_proxy_request(&app, &label, request).await);
+    });
+}
+
+fn _is_app_origin(app: &AppHandle, origin: &str) -> bool {
+    if APP_ORIGINS.contains(&origin) {
+        return true;
+    }
+    // Pages are served by the frontend dev server in debug builds
+    cfg!(debug_assertions)
+        && app
+            .config()
+            .build
+            .dev_url
+            .as_ref()
+            .is_some_and(|url| url.origin().ascii_serialization() == origin)
+}
+
+/// Whether the page currently loaded in the webview is one of ours, so that
+/// an external page it navigated to cannot use the proxy.
+fn _is_app_webview(app: &AppHandle, label: &str) -> bool {
+    app.get_webview(label)
+        .and_then(|webview| webview.url().ok())
+        .is_some_and(|url| _is_app_origin(app, &url.origin().ascii_serialization()))
+}
+
+/// Daemon API each window may call. Only the main window works with the
+/// workspace, the other windows never need the daemon.
+fn _is_request_allowed(label: &str, method: &Method, path: &str) -> bool {
+    match label {
+        "main" => true,
+        "about" => method == Method::GET && path == "/v1/status",
+        _ => false,
+    }
+}
+
+fn _with_cors_headers(
+    builder: tauri::http::response::Builder,
+    origin: Option<&header::HeaderValue>,
+) -> tauri::http::response::Builder {
+    let Some(origin) = origin else {
+        return builder;
+    };
+    builder
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin)
+        .header(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            "Authorization, Content-Type, Range",
+        )
+        .header(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            "GET, POST, PUT, PATCH, DELETE, OPTIONS",
+        )
+        .header(header::VARY, "Origin")
+}
+
+fn _error_response(status: StatusCode, message: String) -> Response<Vec<u8>> {
+    let mut response = Response::new(message.into_bytes());
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+    response
+}
+
+/// CORS headers are set by the proxy for the calling window, and the length
+/// and framing of the collected body are set by the webview.
+fn _is_forwarded_response_header(name: &HeaderName) -> bool {
+    !name.as_str().starts_with("access-control-")
+        && name != header::CONTENT_LENGTH
+        && name != header::TRANSFER_ENCODING
+        && name != header::CONNECTION
+}
+
+async fn _proxy_request(
+    app: &AppHandle,
+    label: &str,
+    request: Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    // Sent by the webview itself without an origin, and never forwarded
+    if request.method() == Method::POST
+        && request.uri().path() == crate::csp::CSP_REPORT_PATH
+        && _is_app_webview(app, label)
+    {
+        crate::csp::_log_csp_report(label, request.body());
+        return Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Vec::new())
+            .unwrap();
+    }
+
+    // The app only calls the daemon with `fetch`, which always sends the
+    // origin. Navigations, iframes and subresources cannot use the proxy.
+    let origin = request.headers().get(header::ORIGIN).cloned();
+    let is_allowed_origin = origin.as_ref().is_some_and(|origin| {
+        origin
+            .to_str()
+            .is_ok_and(|origin| _is_app_origin(app, origin))
+    });
+    let is_cors_request = request
+        .headers()
+        .get("Sec-Fetch-Mode")
+        .is_none_or(|mode| mode == "cors");
+    if !is_allowed_origin || !is_cors_request || !_is_app_webview(app, label) {
+        log::warn!(
+            "Rejected proxy request from webview {} with origin {:?}",
+            label,
+            origin
+        );
+        return _error_response(StatusCode::FORBIDDEN, "Forbidden origin".to_string());
+    }
+    if request.method() == Method::OPTIONS {
+        return _with_cors_headers(Response::builder(), origin.as_ref())
+            .status(StatusCode::NO_CONTENT)
+            .body(Vec::new())
+            .unwrap();
+    }
+
+    if !_is_request_allowed(label, request.method(), request.uri().path()) {
+        log::warn!(
+            "Rejected proxy request {} {} from webview {}",
+            request.method(),
+            request.uri().path(),
+            label
+        );
+        return _error_response(
+            StatusCode::FORBIDDEN,
+            "Not allowed for this window".to_string(),
+        );
+    }
+
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path_and_query| path_and_query.as_str())
+        .to_string();
+    let (parts, body) = request.into_parts();
+    let mut builder =
+        crate::daemon_client::_daemon_request(&connection, parts.method, &path_and_query);
+    if let Some(headers) = builder.headers_mut() {
+        for name in FORWARDED_HEADERS {
+            if let Some(value) = parts.headers.get(&name) {
+                headers.insert(name, value.clone());
+            }
+        }
+    }
+    let daemon_request = match builder.body(Full::new(Bytes::from(body))) {
+        Ok(daemon_request) => daemon_request,
+        Err(e) => return _error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+
+    let response =
+        match crate::daemon_client::_send_daemon_request(&connection, daemon_request).await {
+            Ok(response) => response,
+            Err(e) => {
+                log::debug!("Proxy request to {} failed: {}", path_and_query, e);
+                return _error_response(StatusCode::BAD_GATEWAY, e);
+            }
+        };
+    let (parts, body) = response.into_parts();
+    let body = match Limited::new(body, MAX_RESPONSE_BYTES).collect().await {
+        Ok(body) => body.to_bytes().to_vec(),
+        Err(e) if e.is::<LengthLimitError>() => {
+            log::warn!("Proxy response for {} is too large", path_and_query);
+            return _error_response(
+                StatusCode::BAD_GATEWAY,
+                format!(
+                    "Daemon response is larger than {} MiB, request it in parts with a Range header",
+                    MAX_RESPONSE_BYTES / 1024 / 1024
+                ),
+            );
+        }
+        Err(e) => return _error_response(StatusCode::BAD_GATEWAY, e.to_string()),
+    };
+
+    let mut builder = _with_cors_headers(Response::builder(), origin.as_ref()).status(parts.status);
+    for (name, value) in parts.headers.iter() {
+        if _is_forwarded_response_header(name) {
+            builder = builder.header(name, value);
+        }
+    }
+    builder.body(body).unwrap_or_else(|e| {
+        log::error!("Invalid proxy response for {}: {}", path_and_query, e);
+        _error_response(StatusCode::BAD_GATEWAY, e.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_cors_and_framing_headers_of_the_daemon() {
+        for name in [
+            "access-control-allow-origin",
+            "access-control-allow-credentials",
+            "content-length",
+            "transfer-encoding",
+            "connection",
+        ] {
+            assert!(!_is_forwarded_response_header(&HeaderName::from_static(
+                name
+            )));
+        }
+        for name in ["content-type", "content-range", "etag", "cache-control"] {
+            assert!(_is_forwarded_response_header(&HeaderName::from_static(
+                name
+            )));
+        }
+    }
+
+    #[test]
+    fn builds_error_responses() {
+        let response = _error_response(StatusCode::BAD_GATEWAY, "Daemon is down".to_string());
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/plain");
+        assert_eq!(response.body(), b"Daemon is down");
+    }
+}
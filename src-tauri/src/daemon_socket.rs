@@ -0,0 +1,102 @@
+//! Per-user socket the bundled daemon listens on instead of a TCP port
+
+use crate::version::DAEMON_VERSION;
+use semver::Version;
+use tauri::AppHandle;
+
+/// First syftboxd version whose `--http-addr` accepts `unix://` and `npipe://`
+/// addresses. No daemon release is verified to accept them yet, so until this
+/// is set every daemon is started on the loopback port.
+const SOCKET_DAEMON_VERSION: Option<Version> = None;
+
+pub fn _daemon_supports_socket(version: &str) -> bool {
+    let Some(socket_version) = SOCKET_DAEMON_VERSION else {
+        return false;
+    };
+    Version::parse(version).is_ok_and(|version| version >= socket_version)
+}
+
+/// The daemon only listens on a socket when it is bundled, recent enough, and
+/// nothing asks for network access to it: a custom `DAEMON_HOST`, a policy
+/// port, or the headless mode that is used from a browser.
+pub fn _should_use_socket(app: &AppHandle, has_port_override: bool) -> bool {
+    let version = crate::daemon_updates::_get_installed_daemon(app)
+        .map_or_else(|| DAEMON_VERSION.to_string(), |(version, _)| version);
+    std::env::var("DAEMON_HOST").is_err()
+        && !has_port_override
+        && !crate::headless::_is_headless(app)
+        && _daemon_supports_socket(&version)
+}
+
+#[cfg(unix)]
+pub fn _daemon_socket_path(app: &AppHandle) -> Result<String, String> {
+    let dir = match dirs::runtime_dir() {
+        Some(dir) => dir.join("syftbox"),
+        None => crate::utils::_app_data_dir(app)?,
+    };
+    Ok(dir.join("daemon.sock").to_string_lossy().into_owned())
+}
+
+/// Pipe names are global to the machine, so the name is not guessable by
+/// other users who could otherwise create the pipe before the daemon does.
+#[cfg(windows)]
+pub fn _daemon_socket_path(_app: &AppHandle) -> Result<String, String> {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    Ok(format!(
+        r"\\.\pipe\syftbox-{}-{}",
+        user,
+        crate::utils::_generate_secure_token()
+    ))
+}
+
+/// Address passed to the daemon's `--http-addr`.
+pub fn _daemon_listen_addr(socket: &str) -> String {
+    #[cfg(unix)]
+    return format!("unix://{}", socket);
+    #[cfg(windows)]
+    return format!("npipe://{}", socket);
+}
+
+/// Makes the socket directory private to the current user and removes a
+/// socket left behind by a previous daemon.
+pub fn _prepare_daemon_socket(socket: &str) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        let path = std::path::Path::new(socket);
+        let dir = path.parent().ok_or("Invalid daemon socket path")?;
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .and_then(|_| std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)))
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        match std::fs::remove_file(path) {
+            Ok(()) => log::debug!("Removed stale daemon socket {}", socket),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to remove stale socket {}: {}", socket, e)),
+        }
+    }
+    // The daemon has to create the first instance of the pipe, one that
+    // already exists belongs to another process
+    #[cfg(windows)]
+    if std::path::Path::new(socket).exists() {
+        return Err(format!("Daemon pipe {} is already in use", socket));
+    }
+    Ok(())
+}
+
+/// Restricts the socket created by the daemon to the current user.
+pub fn _restrict_daemon_socket(socket: &str) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(e) = std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600)) {
+            log::error!("Failed to restrict daemon socket {}: {}", socket, e);
+        }
+    }
+    // Further instances of a pipe can only be created by its owner, and the
+    // name cannot be taken in advance as it is not known before the spawn
+    #[cfg(windows)]
+    let _ = socket;
+}
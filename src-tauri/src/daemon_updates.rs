@@ -0,0 +1,340 @@
+//! Update channel for the syftboxd daemon, independent of desktop releases
+
+use crate::daemon::{_restart_daemon, _wait_for_daemon_health, DaemonProcess};
+use crate::error::SafeLock;
+use crate::version::{DAEMON_VERSION, DESKTOP_VERSION};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use minisign_verify::{PublicKey, Signature};
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const DAEMON_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct DaemonRelease {
+    version: String,
+    min_desktop_version: String,
+    platforms: HashMap<String, DaemonReleasePlatform>,
+}
+
+#[derive(Deserialize)]
+struct DaemonReleasePlatform {
+    url: String,
+    sha256: String,
+    signature: String,
+}
+
+fn _daemon_binary_name() -> &'static str {
+    if cfg!(windows) {
+        "syftboxd.exe"
+    } else {
+        "syftboxd"
+    }
+}
+
+fn _platform_key() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+fn _daemon_updates_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::utils::_app_data_dir(app).map(|dir| dir.join("daemon"))
+}
+
+/// Returns the version and path of a downloaded daemon binary if it is newer
+/// than, and compatible with, the one bundled with this desktop build.
+pub fn _get_installed_daemon(app: &AppHandle) -> Option<(String, PathBuf)> {
+    let updates_dir = _daemon_updates_dir(app)
+        .map_err(|e| log::error!("Failed to look for downloaded daemons: {}", e))
+        .ok()?;
+    let active_version = std::fs::read_to_string(updates_dir.join("active_version.txt")).ok()?;
+    let active_version = active_version.trim();
+
+    let is_usable = Version::parse(active_version)
+        .map(|version| _is_compatible_upgrade(&version))
+        .unwrap_or(false);
+    let path = updates_dir.join(active_version).join(_daemon_binary_name());
+    let expected_hash = std::fs::read_to_string(path.with_extension("sha256")).unwrap_or_default();
+    let is_intact = crate::integrity::_hash_file(&path)
+        .is_some_and(|hash| hash.eq_ignore_ascii_case(expected_hash.trim()));
+    if !is_usable || !is_intact {
+        log::info!(
+            "Discarding downloaded syftboxd {} in favour of bundled {}",
+            active_version,
+            DAEMON_VERSION
+        );
+        if let Err(e) = std::fs::remove_dir_all(&updates_dir) {
+            log::error!(
+                "Failed to remove daemon updates dir {}: {}",
+                updates_dir.display(),
+                e
+            );
+        }
+        return None;
+    }
+    Some((active_version.to_string(), path))
+}
+
+fn _is_compatible_upgrade(version: &Version) -> bool {
+    let (Ok(bundled), Ok(compatible)) = (
+        Version::parse(DAEMON_VERSION),
+        VersionReq::parse(&format!("^{}", DAEMON_VERSION)),
+    ) else {
+        log::error!("Invalid bundled daemon version {}", DAEMON_VERSION);
+        return false;
+    };
+    version > &bundled && compatible.matches(version)
+}
+
+fn _verify_signature(app: &AppHandle, data: &[u8], signature: &str) -> Result<(), String> {
+    let pubkey = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|pubkey| pubkey.as_str())
+        .ok_or("Updater public key is not configured")?;
+    let pubkey = BASE64
+        .decode(pubkey)
+        .map_err(|e| format!("Invalid public key encoding: {}", e))?;
+    let public_key = PublicKey::decode(&String::from_utf8_lossy(&pubkey))
+        .map_err(|e| format!("Invalid public key: {}", e))?;
+    let signature = BASE64
+        .decode(signature)
+        .map_err(|e| format!("Invalid signature encoding: {}", e))?;
+    let signature = Signature::decode(&String::from_utf8_lossy(&signature))
+        .map_err(|e| format!("Invalid signature: {}", e))?;
+    public_key
+        .verify(data, &signature, true)
+        .map_err(|e| format!("Signature verification failed: {}", e))
+}
+
+fn _verify_sha256(data: &[u8], expected: &str) -> Result<(), String> {
+    let actual = hex::encode(Sha256::digest(data));
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Checksum mismatch (expected {}, got {})",
+            expected, actual
+        ))
+    }
+}
+
+async fn _fetch_daemon_release(app: &AppHandle) -> Result<DaemonRelease, String> {
+    let channel = crate::settings::_effective_settings(app).update_channel;
+    let endpoint = crate::policy::_daemon_update_endpoint_for_channel(&channel)?;
+    log::debug!("Using daemon update channel {} ({})", channel, endpoint);
+    reqwest::get(endpoint)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch daemon release info: {}", e))?
+        .json::<DaemonRelease>()
+        .await
+        .map_err(|e| format!("Failed to parse daemon release info: {}", e))
+}
+
+fn _install_daemon_binary(
+    app: &AppHandle,
+    version: &str,
+    data: &[u8],
+    sha256: &str,
+) -> Result<(), String> {
+    let version_dir = _daemon_updates_dir(app)?.join(version);
+    std::fs::create_dir_all(&version_dir)
+        .map_err(|e| format!("Failed to create {}: {}", version_dir.display(), e))?;
+
+    let path = version_dir.join(_daemon_binary_name());
+    let tmp_path = path.with_extension("download");
+    std::fs::write(&tmp_path, data)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make {} executable: {}", tmp_path.display(), e))?;
+    }
+    std::fs::write(path.with_extension("sha256"), sha256)
+        .map_err(|e| format!("Failed to write checksum for {}: {}", path.display(), e))?;
+    std::fs::rename(&tmp_path, &path)
+        .map_err(|e| format!("Failed to move {}: {}", tmp_path.display(), e))
+}
+
+fn _set_active_daemon_version(app: &AppHandle, version: Option<&str>) {
+    let path = match _daemon_updates_dir(app) {
+        Ok(updates_dir) => updates_dir.join("active_version.txt"),
+        Err(e) => {
+            log::error!("Failed to update active daemon version: {}", e);
+            return;
+        }
+    };
+    let result = match version {
+        Some(version) => std::fs::write(&path, version),
+        None => std::fs::remove_file(&path).or_else(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Ok(()),
+            _ => Err(e),
+        }),
+    };
+    if let Err(e) = result {
+        log::error!(
+            "Failed to update active daemon version in {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+pub async fn _check_for_daemon_updates(app: &AppHandle) {
+    if crate::pause::_is_sync_paused(app) {
+        log::debug!("Sync is paused, skipping daemon update check");
+        return;
+    }
+    if crate::daemon_client::_get_daemon_connection(app).external {
+        log::debug!("Using an external daemon, skipping daemon update check");
+        return;
+    }
+    log::info!("Checking for daemon updates");
+    let release = match _fetch_daemon_release(app).await {
+        Ok(release) => release,
+        Err(e) => {
+            log::error!("{}", e);
+            return;
+        }
+    };
+
+    let Ok(new_version) = Version::parse(&release.version) else {
+        log::error!("Invalid daemon release version: {}", release.version);
+        return;
+    };
+    let running_version = app.state::<DaemonProcess>().version.safe_lock().clone();
+    if Version::parse(&running_version).is_ok_and(|running| new_version <= running) {
+        log::info!("No daemon updates available");
+        return;
+    }
+    if !_is_compatible_upgrade(&new_version) {
+        log::info!(
+            "Daemon {} is not compatible with bundled daemon {}, skipping",
+            new_version,
+            DAEMON_VERSION
+        );
+        return;
+    }
+    if Version::parse(&release.min_desktop_version).map_or(true, |min| {
+        Version::parse(DESKTOP_VERSION).is_ok_and(|v| v < min)
+    }) {
+        log::info!(
+            "Daemon {} requires desktop {} or newer, skipping",
+            new_version,
+            release.min_desktop_version
+        );
+        return;
+    }
+    let updates_dir = match _daemon_updates_dir(app) {
+        Ok(updates_dir) => updates_dir,
+        Err(e) => {
+            log::error!("Failed to check for daemon updates: {}", e);
+            return;
+        }
+    };
+    let rejected_path = updates_dir.join("rejected_version.txt");
+    if std::fs::read_to_string(&rejected_path).is_ok_and(|v| v.trim() == release.version) {
+        log::debug!(
+            "Skipping daemon {} as it previously failed its health check",
+            release.version
+        );
+        return;
+    }
+    let Some(platform) = release.platforms.get(&_platform_key()) else {
+        log::info!(
+            "Daemon {} has no build for platform {}",
+            release.version,
+            _platform_key()
+        );
+        return;
+    };
+
+    log::info!(
+        "Daemon update available: {} (current: {})",
+        release.version,
+        running_version
+    );
+    let data = match reqwest::get(&platform.url)
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(response) => match response.bytes().await {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Failed to download daemon {}: {}", release.version, e);
+                return;
+            }
+        },
+        Err(e) => {
+            log::error!("Failed to download daemon {}: {}", release.version, e);
+            return;
+        }
+    };
+
+    if let Err(e) = _verify_sha256(&data, &platform.sha256)
+        .and_then(|_| _verify_signature(app, &data, &platform.signature))
+        .and_then(|_| _install_daemon_binary(app, &release.version, &data, &platform.sha256))
+    {
+        log::error!("Failed to install daemon {}: {}", release.version, e);
+        return;
+    }
+
+    _swap_daemon(app, &release.version).await;
+}
+
+async fn _swap_daemon(app: &AppHandle, version: &str) {
+    let updates_dir = match _daemon_updates_dir(app) {
+        Ok(updates_dir) => updates_dir,
+        Err(e) => {
+            log::error!("Failed to switch to syftboxd {}: {}", version, e);
+            return;
+        }
+    };
+    let previous_version = std::fs::read_to_string(updates_dir.join("active_version.txt")).ok();
+    // The new binary could not be health checked, a later check installs it
+    if crate::pause::_is_sync_paused(app) {
+        log::info!("Sync is paused, not switching to syftboxd {}", version);
+        return;
+    }
+    _set_active_daemon_version(app, Some(version));
+
+    let daemon_process = app.state::<DaemonProcess>();
+    daemon_process.on_probation.store(true, Ordering::SeqCst);
+    let is_healthy = match _restart_daemon(app).await {
+        Ok(()) => _wait_for_daemon_health(app, DAEMON_HEALTH_CHECK_TIMEOUT).await,
+        Err(e) => {
+            log::error!("{}", e);
+            false
+        }
+    };
+    daemon_process.on_probation.store(false, Ordering::SeqCst);
+
+    if is_healthy {
+        log::info!("Switched to syftboxd {}", version);
+        if let Some(previous_version) = previous_version {
+            let _ = std::fs::remove_dir_all(updates_dir.join(previous_version.trim()));
+        }
+        return;
+    }
+
+    log::error!(
+        "syftboxd {} failed its health check, falling back to the previous binary",
+        version
+    );
+    let _ = std::fs::write(updates_dir.join("rejected_version.txt"), version);
+    let _ = std::fs::remove_dir_all(updates_dir.join(version));
+    _set_active_daemon_version(app, previous_version.as_deref().map(str::trim));
+    if let Err(e) = _restart_daemon(app).await {
+        log::error!("Failed to restart syftboxd after rollback: {}", e);
+    }
+}
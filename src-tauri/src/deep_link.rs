@@ -0,0 +1,325 @@
+//! Validation and routing of syft:// deep links
+
+use crate::error::SafeLock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+pub const DEEP_LINK_EVENT: &str = "deep-link";
+
+const MAX_DEEP_LINK_LENGTH: usize = 2048;
+const MAX_APP_ID_LENGTH: usize = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MarketplaceAction {
+    View,
+    Install,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum DeepLinkRoute {
+    /// `syft://workspace/<path>`
+    Workspace { path: String },
+    /// `syft://marketplace/<app id>?action=view|install`
+    Marketplace {
+        app_id: String,
+        action: MarketplaceAction,
+    },
+    /// `syft://install?source=<https repository url>`
+    AppInstall { source: String },
+    /// `syft://<email>/<path>`, a path shared from another datasite
+    Share { email: String, path: String },
+}
+
+/// Links are queued until the main window frontend has asked for them, as
+/// events emitted before it has loaded are lost.
+#[derive(Default)]
+pub struct DeepLinkState {
+    pending: Mutex<Vec<DeepLinkRoute>>,
+    is_frontend_ready: AtomicBool,
+}
+
+/// Decodes a relative path, refusing anything that could escape the
+/// directory it is resolved against.
+fn _parse_relative_path(encoded: &str) -> Result<String, String> {
+    let mut segments = Vec::new();
+    for segment in encoded.split('/').filter(|segment| !segment.is_empty()) {
+        let segment =
+            urlencoding::decode(segment).map_err(|_| "path is not valid UTF-8".to_string())?;
+        if segment == "." || segment == ".." {
+            return Err("path must not contain '.' or '..' segments".to_string());
+        }
+        if segment.contains(['/', '\\', ':']) || segment.chars().any(char::is_control) {
+            return Err(format!("path segment {:?} is not allowed", segment));
+        }
+        segments.push(segment.into_owned());
+    }
+    Ok(segments.join("/"))
+}
+
+fn _is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && email
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@.-_+".contains(c))
+}
+
+fn _parse_app_install_source(url: &Url) -> Result<String, String> {
+    let source = url
+        .query_pairs()
+        .find(|(key, _)| key == "source")
+        .map(|(_, value)| value.into_owned())
+        .ok_or("app install link has no source")?;
+    let source_url = Url::parse(&source).map_err(|e| format!("invalid app source: {}", e))?;
+    if source_url.scheme() != "https" {
+        return Err("app sources must use https".to_string());
+    }
+    if !source_url.username().is_empty() || source_url.password().is_some() {
+        return Err("app sources must not contain credentials".to_string());
+    }
+    Ok(source_url.to_string())
+}
+
+pub fn _parse_deep_link(link: &str) -> Result<DeepLinkRoute, String> {
+    if link.len() > MAX_DEEP_LINK_LENGTH {
+        return Err("link is too long".to_string());
+    }
+    let url = Url::parse(link).map_err(|e| e.to_string())?;
+    if url.scheme() != "syft" {
+        return Err(format!("unsupported scheme {}", url.scheme()));
+    }
+    let host = url.host_str().unwrap_or_default();
+
+    if !url.username().is_empty() {
+        let email = format!("{}@{}", url.username(), host);
+        if !_is_valid_email(&email) {
+            return Err(format!("invalid datasite {}", email));
+        }
+        return Ok(DeepLinkRoute::Share {
+            email,
+            path: _parse_relative_path(url.path())?,
+        });
+    }
+
+    match host {
+        "workspace" => Ok(DeepLinkRoute::Workspace {
+            path: _parse_relative_path(url.path())?,
+        }),
+        "marketplace" => {
+            let app_id = url.path().trim_matches('/');
+            let is_valid_app_id = !app_id.is_empty()
+                && app_id.len() <= MAX_APP_ID_LENGTH
+                && app_id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+            if !is_valid_app_id {
+                return Err(format!("invalid marketplace app id {:?}", app_id));
+            }
+            let action = match url
+                .query_pairs()
+                .find(|(key, _)| key == "action")
+                .map(|(_, value)| value.into_owned())
+                .as_deref()
+            {
+                None | Some("view") => MarketplaceAction::View,
+                Some("install") => MarketplaceAction::Install,
+                Some(action) => return Err(format!("unknown marketplace action {}", action)),
+            };
+            Ok(DeepLinkRoute::Marketplace {
+                app_id: app_id.to_string(),
+                action,
+            })
+        }
+        "install" => Ok(DeepLinkRoute::AppInstall {
+            source: _parse_app_install_source(&url)?,
+        }),
+        _ => Err(format!("unknown link target {:?}", host)),
+    }
+}
+
+/// Links are opened from other apps, so the user is told why nothing happened.
+fn _show_rejected_link_dialog(app: &AppHandle, reason: &str) {
+    app.dialog()
+        .message(format!("This SyftBox link cannot be opened: {}.", reason))
+        .kind(MessageDialogKind::Warning)
+        .title("SyftBox")
+        .show(|_| {});
+}
+
+/// Validates a link and routes it to the main window, showing it if needed.
+pub fn _open_deep_link(app: &AppHandle, link: &str) {
+    let route = match _parse_deep_link(link) {
+        Ok(route) => route,
+        Err(e) => {
+            log::warn!("Rejected deep link {:?}: {}", link, e);
+            _show_rejected_link_dialog(app, &e);
+            return;
+        }
+    };
+    log::info!("Opening deep link: {:?}", route);
+    if crate::headless::_is_headless(app) {
+        log::warn!("Deep links cannot be opened in headless mode");
+        _show_rejected_link_dialog(app, "SyftBox is running in headless mode");
+        return;
+    }
+
+    crate::utils::show_main_window(app);
+    let state = app.state::<DeepLinkState>();
+    if !state.is_frontend_ready.load(Ordering::SeqCst) {
+        state.pending.safe_lock().push(route);
+        return;
+    }
+    if let Err(e) = app.emit_to("main", DEEP_LINK_EVENT, route) {
+        log::error!("Failed to send deep link to main window: {}", e);
+    }
+}
+
+/// Returns the links received before the frontend was listening. Later links
+/// are delivered as events.
+pub fn _take_pending_deep_links(app: &AppHandle) -> Vec<DeepLinkRoute> {
+    let state = app.state::<DeepLinkState>();
+    state.is_frontend_ready.store(true, Ordering::SeqCst);
+    std::mem::take(&mut *state.pending.safe_lock())
+}
+
+pub fn _setup_deep_links(app: &AppHandle) {
+    let app_handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            _open_deep_link(&app_handle, url.as_str());
+        }
+    });
+
+    // Links that launched the app
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            for url in urls {
+                _open_deep_link(app, url.as_str());
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to get launch deep links: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_supported_links() {
+        assert_eq!(
+            _parse_deep_link("syft://workspace/apps/my%20app/"),
+            Ok(DeepLinkRoute::Workspace {
+                path: "apps/my app".to_string()
+            })
+        );
+        assert_eq!(
+            _parse_deep_link("syft://marketplace/fl-client?action=install"),
+            Ok(DeepLinkRoute::Marketplace {
+                app_id: "fl-client".to_string(),
+                action: MarketplaceAction::Install,
+            })
+        );
+        assert_eq!(
+            _parse_deep_link("syft://alice@openmined.org/public/data.csv"),
+            Ok(DeepLinkRoute::Share {
+                email: "alice@openmined.org".to_string(),
+                path: "public/data.csv".to_string(),
+            })
+        );
+        assert_eq!(
+            _parse_deep_link(
+                "syft://install?source=https%3A%2F%2Fgithub.com%2FOpenMined%2Fexample"
+            ),
+            Ok(DeepLinkRoute::AppInstall {
+                source: "https://github.com/OpenMined/example".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_links() {
+        assert!(_parse_deep_link("").is_err());
+        assert!(_parse_deep_link("not a link").is_err());
+        assert!(_parse_deep_link("https://workspace/apps").is_err());
+        assert!(_parse_deep_link("syft://%zz@openmined.org/").is_err());
+        assert!(_parse_deep_link("syft://alice@localhost/").is_err());
+        assert!(_parse_deep_link("syft://marketplace/").is_err());
+        assert!(_parse_deep_link("syft://marketplace/a%2Fb").is_err());
+        assert!(_parse_deep_link("syft://marketplace/app?action=delete").is_err());
+        let long_link = format!("syft://workspace/{}", "a".repeat(MAX_DEEP_LINK_LENGTH));
+        assert!(_parse_deep_link(&long_link).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_hosts() {
+        assert!(_parse_deep_link("syft://settings/").is_err());
+        assert!(_parse_deep_link("syft://").is_err());
+        assert!(_parse_deep_link("syft:workspace").is_err());
+    }
+
+    #[test]
+    fn rejects_path_traversal() {
+        assert!(_parse_deep_link("syft://workspace/a%2F..%2Fb").is_err());
+        assert!(_parse_deep_link("syft://workspace/a%5C..%5Cb").is_err());
+        assert!(_parse_deep_link("syft://workspace/C%3A/Windows").is_err());
+        assert!(_parse_relative_path("a/%2E%2E/b").is_err());
+        assert!(_parse_relative_path("./a").is_err());
+    }
+
+    #[test]
+    fn resolves_dot_segments_inside_the_root() {
+        // The URL parser resolves dot segments, which cannot go above the root
+        assert_eq!(
+            _parse_deep_link("syft://workspace/%2E%2E/%2e%2e/secrets"),
+            Ok(DeepLinkRoute::Workspace {
+                path: "secrets".to_string()
+            })
+        );
+        assert_eq!(
+            _parse_deep_link("syft://alice@openmined.org/public/../../bob"),
+            Ok(DeepLinkRoute::Share {
+                email: "alice@openmined.org".to_string(),
+                path: "bob".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unsafe_app_sources() {
+        assert!(_parse_deep_link("syft://install").is_err());
+        assert!(_parse_deep_link("syft://install?source=github.com%2Fa%2Fb").is_err());
+        assert!(_parse_deep_link("syft://install?source=http%3A%2F%2Fgithub.com%2Fa").is_err());
+        assert!(_parse_deep_link("syft://install?source=file%3A%2F%2F%2Fetc%2Fpasswd").is_err());
+        assert!(
+            _parse_deep_link("syft://install?source=https%3A%2F%2Fuser%3Apw%40github.com%2Fa")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn resolves_traversal_in_app_sources_on_the_same_host() {
+        assert_eq!(
+            _parse_deep_link(
+                "syft://install?source=https%3A%2F%2Fgithub.com%2Fa%2F..%2F..%2F..%2Fb%2Fc"
+            ),
+            Ok(DeepLinkRoute::AppInstall {
+                source: "https://github.com/b/c".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Diagnostics about the desktop app and its sidecars
+
+use crate::error::SafeLock;
+use crate::integrity::{_verify_all_sidecars, SidecarIntegrity};
+use crate::version::{
+    DAEMON_BUILD, DAEMON_HASH, DAEMON_VERSION, DESKTOP_BUILD, DESKTOP_HASH, DESKTOP_VERSION,
+};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub desktop_version: String,
+    pub desktop_hash: String,
+    pub daemon_version: String,
+    pub daemon_hash: String,
+    pub sidecars: Vec<SidecarIntegrity>,
+}
+
+pub fn _collect_diagnostics() -> Diagnostics {
+    log::debug!("Collecting diagnostics");
+    Diagnostics {
+        desktop_version: DESKTOP_VERSION.to_string(),
+        desktop_hash: DESKTOP_HASH.to_string(),
+        daemon_version: DAEMON_VERSION.to_string(),
+        daemon_hash: DAEMON_HASH.to_string(),
+        sidecars: _verify_all_sidecars(),
+    }
+}
+
+/// Short plain text summary meant to be pasted into support requests.
+pub fn _diagnostics_summary(app: &AppHandle) -> String {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    let daemon_status = app
+        .state::<crate::tray::TrayStatusState>()
+        .daemon_status
+        .safe_lock()
+        .clone();
+    #[cfg(not(debug_assertions))]
+    let running_daemon_version = app
+        .state::<crate::daemon::DaemonProcess>()
+        .version
+        .safe_lock()
+        .clone();
+    #[cfg(debug_assertions)]
+    let running_daemon_version = "external".to_string();
+
+    [
+        format!(
+            "SyftBox Desktop: {} ({}, built {})",
+            DESKTOP_VERSION, DESKTOP_HASH, DESKTOP_BUILD
+        ),
+        format!(
+            "Bundled daemon: {} ({}, built {})",
+            DAEMON_VERSION, DAEMON_HASH, DAEMON_BUILD
+        ),
+        format!("Running daemon: {}", running_daemon_version),
+        format!(
+            "OS: {} {} ({})",
+            tauri_plugin_os::platform(),
+            tauri_plugin_os::version(),
+            tauri_plugin_os::arch()
+        ),
+        format!("Daemon status: {:?}", daemon_status),
+        format!("Daemon port: {}", connection.port),
+        format!("Sync paused: {}", crate::pause::_is_sync_paused(app)),
+    ]
+    .join("\n")
+}
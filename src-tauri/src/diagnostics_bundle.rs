@@ -0,0 +1,573 @@
+//! Zip of everything support needs to look into a problem
+//!
+//! Everything is read from disk or collected by the shell itself, so that a
+//! bundle can still be created when the daemon is not running. Email
+//! addresses, the host name and the home directory are removed from every
+//! file, as bundles are attached to bug reports.
+
+use crate::error::SafeLock;
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use sysinfo::{Disks, System};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Only the end of larger log files is included.
+const MAX_LOG_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Logs are added newest first until this is reached, so that old rotated
+/// logs are left out.
+const MAX_LOGS_BYTES: u64 = 40 * 1024 * 1024;
+const PORT_CHECK_TIMEOUT: Duration = Duration::from_secs(1);
+const REDACTED: &str = "<redacted>";
+const REDACTED_EMAIL: &str = "<email>";
+/// Config keys whose values are replaced with [`REDACTED`].
+const REDACTED_KEY_PARTS: [&str; 6] = ["token", "secret", "password", "key", "credential", "email"];
+
+type Bundle = ZipWriter<Cursor<Vec<u8>>>;
+
+/// Personal details that are replaced in the text of every file.
+struct Redactor {
+    host_name: Option<String>,
+    home_dir: Option<String>,
+}
+
+impl Redactor {
+    fn new() -> Self {
+        Self {
+            host_name: System::host_name()
+                .filter(|host_name| host_name.len() > 2 && host_name != "localhost"),
+            home_dir: dirs::home_dir().map(|home| home.to_string_lossy().into_owned()),
+        }
+    }
+
+    fn redact(&self, text: &str) -> String {
+        let mut text = _redact_emails(text);
+        if let Some(home_dir) = &self.home_dir {
+            text = text.replace(home_dir.as_str(), "~");
+        }
+        if let Some(host_name) = &self.host_name {
+            text = text.replace(host_name.as_str(), REDACTED);
+        }
+        text
+    }
+}
+
+fn _is_email_local_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "._%+-".contains(c)
+}
+
+fn _is_email_domain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ".-".contains(c)
+}
+
+/// Replaces anything shaped like `local@domain.tld` with [`REDACTED_EMAIL`].
+fn _redact_emails(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('@') {
+        // Both parts are ASCII, so chars and bytes can be counted alike
+        let local_start = at
+            - rest[..at]
+                .chars()
+                .rev()
+                .take_while(|c| _is_email_local_char(*c))
+                .count();
+        let domain_end = at
+            + 1
+            + rest[at + 1..]
+                .chars()
+                .take_while(|c| _is_email_domain_char(*c))
+                .count();
+        let domain = rest[at + 1..domain_end].trim_end_matches('.');
+        if local_start < at && domain.contains('.') {
+            redacted.push_str(&rest[..local_start]);
+            redacted.push_str(REDACTED_EMAIL);
+            rest = &rest[at + 1 + domain.len()..];
+        } else {
+            redacted.push_str(&rest[..=at]);
+            rest = &rest[at + 1..];
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+fn _add_file(
+    bundle: &mut Bundle,
+    redactor: &Redactor,
+    name: &str,
+    contents: &[u8],
+) -> Result<(), String> {
+    let contents = redactor.redact(&String::from_utf8_lossy(contents));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    bundle
+        .start_file(name, options)
+        .and_then(|_| bundle.write_all(contents.as_bytes()).map_err(Into::into))
+        .map_err(|e| format!("Failed to add {} to the bundle: {}", name, e))
+}
+
+fn _read_tail(path: &Path, max_bytes: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(max_bytes)))?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+/// Collects the log and crash files in the logs dir and its subdirectories.
+fn _log_files(dir: &Path, files: &mut Vec<(SystemTime, PathBuf)>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Failed to read logs dir {}: {}", dir.display(), e);
+            return;
+        }
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            _log_files(&path, files);
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        files.push((modified, path));
+    }
+}
+
+/// Adds the desktop and sidecar logs and the crash files, which all live in
+/// the logs dir, newest first up to [`MAX_LOGS_BYTES`].
+fn _add_logs(
+    bundle: &mut Bundle,
+    redactor: &Redactor,
+    dir: &Path,
+    prefix: &str,
+) -> Result<(), String> {
+    let mut files = Vec::new();
+    _log_files(dir, &mut files);
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut remaining = MAX_LOGS_BYTES;
+    for (_, path) in files {
+        if remaining == 0 {
+            log::info!("Leaving older log {} out of the bundle", path.display());
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(dir) else {
+            continue;
+        };
+        let name = format!(
+            "{}/{}",
+            prefix,
+            relative.to_string_lossy().replace('\\', "/")
+        );
+        match _read_tail(&path, MAX_LOG_FILE_BYTES.min(remaining)) {
+            Ok(contents) => {
+                remaining = remaining.saturating_sub(contents.len() as u64);
+                _add_file(bundle, redactor, &name, &contents)?;
+            }
+            Err(e) => log::warn!("Failed to read log file {}: {}", path.display(), e),
+        }
+    }
+    Ok(())
+}
+
+fn _redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if REDACTED_KEY_PARTS.iter().any(|part| key.contains(part)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    _redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(_redact),
+        _ => {}
+    }
+}
+
+/// Daemon config of the active profile, with its tokens redacted.
+fn _redacted_daemon_config(app: &AppHandle) -> String {
+    let profile = app
+        .state::<crate::state::ActiveProfile>()
+        .0
+        .safe_lock()
+        .clone();
+    let path = match profile {
+        Some(profile) => crate::cli::profile_config_path(&profile),
+        None => dirs::home_dir().map(|home| home.join(".syftbox").join("config.json")),
+    };
+    let Some(path) = path else {
+        return "<home directory not found>".to_string();
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => return format!("<{} could not be read: {}>", path.display(), e),
+    };
+    match serde_json::from_str::<Value>(&content) {
+        Ok(mut config) => {
+            _redact(&mut config);
+            serde_json::to_string_pretty(&config).unwrap_or_default()
+        }
+        Err(e) => format!("<{} is not valid JSON: {}>", path.display(), e),
+    }
+}
+
+fn _redacted_settings(app: &AppHandle) -> String {
+    let mut settings =
+        serde_json::to_value(crate::settings::_get_settings_state(app)).unwrap_or(Value::Null);
+    _redact(&mut settings);
+    serde_json::to_string_pretty(&settings).unwrap_or_default()
+}
+
+fn _versions(app: &AppHandle) -> String {
+    format!(
+        "{}\nOS version: {}\n",
+        crate::diagnostics::_diagnostics_summary(app),
+        System::long_os_version().unwrap_or_else(|| "unknown".to_string())
+    )
+}
+
+fn _system_snapshot() -> String {
+    let mut system = System::new_all();
+    system.refresh_all();
+    let mut snapshot = String::new();
+    let _ = writeln!(snapshot, "Kernel: {}", System::kernel_long_version());
+    let _ = writeln!(snapshot, "Uptime: {}s", System::uptime());
+    let _ = writeln!(
+        snapshot,
+        "CPUs: {} x {}",
+        system.cpus().len(),
+        system
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand())
+            .unwrap_or("unknown")
+    );
+    let _ = writeln!(snapshot, "CPU usage: {:.1}%", system.global_cpu_usage());
+    let _ = writeln!(
+        snapshot,
+        "Memory: {} / {} MiB used, swap {} / {} MiB used",
+        system.used_memory() / 1024 / 1024,
+        system.total_memory() / 1024 / 1024,
+        system.used_swap() / 1024 / 1024,
+        system.total_swap() / 1024 / 1024
+    );
+    let _ = writeln!(snapshot, "\nDisks:");
+    for disk in Disks::new_with_refreshed_list().list() {
+        let _ = writeln!(
+            snapshot,
+            "{} ({}): {} / {} MiB free",
+            disk.mount_point().display(),
+            disk.file_system().to_string_lossy(),
+            disk.available_space() / 1024 / 1024,
+            disk.total_space() / 1024 / 1024
+        );
+    }
+    let _ = writeln!(snapshot, "\nSyftBox processes:");
+    for process in system.processes().values() {
+        let name = process.name().to_string_lossy();
+        if name.to_lowercase().contains("syftbox") || name.contains("process-wick") {
+            let _ = writeln!(
+                snapshot,
+                "{} {}: {:.1}% CPU, {} MiB",
+                process.pid(),
+                name,
+                process.cpu_usage(),
+                process.memory() / 1024 / 1024
+            );
+        }
+    }
+    snapshot
+}
+
+/// Whether the daemon's port or socket accepts connections, without talking
+/// to the daemon itself.
+fn _port_status(app: &AppHandle) -> String {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    let mut status = format!(
+        "Daemon host: {}\nDaemon port: {}\nExternal daemon: {}\nRemote access: {}\n",
+        connection.host,
+        connection.port,
+        connection.external,
+        crate::remote_access::_is_remote_access_active(app)
+    );
+    if let Some(socket) = &connection.socket {
+        let _ = writeln!(
+            status,
+            "Socket: {} ({})",
+            socket,
+            if Path::new(socket).exists() {
+                "exists"
+            } else {
+                "missing"
+            }
+        );
+        return status;
+    }
+    let address = format!("{}:{}", connection.client_host(), connection.port);
+    let result = address
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())
+        .and_then(|mut addrs| addrs.next().ok_or_else(|| "no address found".to_string()))
+        .and_then(|addr| {
+            TcpStream::connect_timeout(&addr, PORT_CHECK_TIMEOUT).map_err(|e| e.to_string())
+        });
+    let _ = writeln!(
+        status,
+        "{}: {}",
+        address,
+        match result {
+            Ok(_) => "accepting connections".to_string(),
+            Err(e) => format!("not accepting connections ({})", e),
+        }
+    );
+    status
+}
+
+/// The desktop version last run and the daemon versions downloaded since, the
+/// update checks themselves are in the logs.
+fn _update_history(app: &AppHandle) -> String {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return "<app data dir not found>".to_string();
+    };
+    let read = |path: PathBuf| {
+        std::fs::read_to_string(&path)
+            .map(|content| content.trim().to_string())
+            .unwrap_or_else(|_| "none".to_string())
+    };
+    let daemon_dir = app_data_dir.join("daemon");
+    let mut downloaded: Vec<String> = std::fs::read_dir(&daemon_dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    downloaded.sort();
+    let pending_update = app
+        .state::<crate::tray::TrayStatusState>()
+        .update_available
+        .safe_lock()
+        .clone();
+    format!(
+        "Last desktop version run: {}\nPending desktop update: {}\nActive downloaded daemon: {}\nDownloaded daemons: {}\n",
+        read(app_data_dir.join("desktop_version.txt")),
+        pending_update.as_deref().unwrap_or("none"),
+        read(daemon_dir.join("active_version.txt")),
+        if downloaded.is_empty() {
+            "none".to_string()
+        } else {
+            downloaded.join(", ")
+        }
+    )
+}
+
+/// Builds the bundle in memory, to be saved or attached to a bug report.
+pub fn _build_diagnostics_bundle(app: &AppHandle) -> Result<Vec<u8>, String> {
+    log::info!("Creating diagnostics bundle");
+    let redactor = Redactor::new();
+    let mut bundle = ZipWriter::new(Cursor::new(Vec::new()));
+    _add_file(
+        &mut bundle,
+        &redactor,
+        "versions.txt",
+        _versions(app).as_bytes(),
+    )?;
+    _add_file(
+        &mut bundle,
+        &redactor,
+        "sidecars.json",
+        &serde_json::to_vec_pretty(&crate::diagnostics::_collect_diagnostics())
+            .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?,
+    )?;
+    _add_file(
+        &mut bundle,
+        &redactor,
+        "system.txt",
+        _system_snapshot().as_bytes(),
+    )?;
+    _add_file(
+        &mut bundle,
+        &redactor,
+        "ports.txt",
+        _port_status(app).as_bytes(),
+    )?;
+    _add_file(
+        &mut bundle,
+        &redactor,
+        "updates.txt",
+        _update_history(app).as_bytes(),
+    )?;
+    _add_file(
+        &mut bundle,
+        &redactor,
+        "settings.json",
+        _redacted_settings(app).as_bytes(),
+    )?;
+    _add_file(
+        &mut bundle,
+        &redactor,
+        "daemon_config.json",
+        _redacted_daemon_config(app).as_bytes(),
+    )?;
+    _add_logs(&mut bundle, &redactor, &crate::utils::_logs_dir(), "logs")?;
+    bundle
+        .finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("Failed to finish the bundle: {}", e))
+}
+
+/// Saves a new bundle to the Downloads folder and returns its path.
+pub fn _save_diagnostics_bundle(app: &AppHandle) -> Result<PathBuf, String> {
+    let bundle = _build_diagnostics_bundle(app)?;
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "Downloads folder not found".to_string())?;
+    let path = dir.join(format!(
+        "syftbox-diagnostics-{}.zip",
+        time::OffsetDateTime::now_utc().unix_timestamp()
+    ));
+    std::fs::write(&path, bundle)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    log::info!("Diagnostics bundle saved to {}", path.display());
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zip::ZipArchive;
+
+    fn _temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("syftbox-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn redacts_emails() {
+        assert_eq!(
+            _redact_emails("Signed in as alice.b+test@openmined.org."),
+            "Signed in as <email>."
+        );
+        assert_eq!(
+            _redact_emails("a@b.co,c_d@mail.example.com"),
+            "<email>,<email>"
+        );
+        assert_eq!(_redact_emails("user@localhost"), "user@localhost");
+        assert_eq!(_redact_emails("@mention and a@ b"), "@mention and a@ b");
+        assert_eq!(
+            _redact_emails("datasites/bob@openmined.org/public"),
+            "datasites/<email>/public"
+        );
+    }
+
+    #[test]
+    fn redacts_home_dir_and_host_name() {
+        let redactor = Redactor {
+            host_name: Some("alices-laptop".to_string()),
+            home_dir: Some("/home/alice".to_string()),
+        };
+        assert_eq!(
+            redactor.redact("Opened /home/alice/SyftBox on alices-laptop for alice@openmined.org"),
+            "Opened ~/SyftBox on <redacted> for <email>"
+        );
+        let redactor = Redactor {
+            host_name: None,
+            home_dir: None,
+        };
+        assert_eq!(redactor.redact("/home/alice"), "/home/alice");
+    }
+
+    #[test]
+    fn redacts_secret_config_values() {
+        let mut config = serde_json::json!({
+            "email": "alice@openmined.org",
+            "server_url": "https://syftbox.net",
+            "refresh_token": "abc",
+            "apps": [{ "apiKey": "def", "name": "fl" }],
+        });
+        _redact(&mut config);
+        assert_eq!(
+            config,
+            serde_json::json!({
+                "email": REDACTED,
+                "server_url": "https://syftbox.net",
+                "refresh_token": REDACTED,
+                "apps": [{ "apiKey": REDACTED, "name": "fl" }],
+            })
+        );
+    }
+
+    #[test]
+    fn reads_only_the_tail_of_large_files() {
+        let dir = _temp_dir("tail");
+        let path = dir.join("log.txt");
+        std::fs::write(&path, "0123456789").unwrap();
+        assert_eq!(_read_tail(&path, 4).unwrap(), b"6789");
+        assert_eq!(_read_tail(&path, 100).unwrap(), b"0123456789");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn caps_log_files_and_their_total_size() {
+        let dir = _temp_dir("logs");
+        let now = SystemTime::now();
+        let mut add_log = |name: &str, len: u64, age_secs: u64| {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            let file = std::fs::File::create(&path).unwrap();
+            file.set_len(len).unwrap();
+            file.set_modified(now - Duration::from_secs(age_secs))
+                .unwrap();
+        };
+        add_log("SyftBoxDesktop.log", 1024, 0);
+        for age in 1..=5 {
+            add_log(
+                &format!("syftboxd-{}.log", age),
+                MAX_LOG_FILE_BYTES + 1,
+                age,
+            );
+        }
+        add_log("crashes/crash-1.txt", 1024, 10);
+
+        let redactor = Redactor {
+            host_name: None,
+            home_dir: None,
+        };
+        let mut bundle = ZipWriter::new(Cursor::new(Vec::new()));
+        _add_logs(&mut bundle, &redactor, &dir, "logs").unwrap();
+        let mut archive = ZipArchive::new(bundle.finish().unwrap()).unwrap();
+
+        let mut sizes = std::collections::HashMap::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index(i).unwrap();
+            sizes.insert(file.name().to_string(), file.size());
+        }
+        assert_eq!(sizes["logs/SyftBoxDesktop.log"], 1024);
+        assert_eq!(sizes["logs/syftboxd-1.log"], MAX_LOG_FILE_BYTES);
+        assert_eq!(sizes["logs/syftboxd-2.log"], MAX_LOG_FILE_BYTES);
+        assert_eq!(sizes["logs/syftboxd-3.log"], MAX_LOG_FILE_BYTES);
+        assert_eq!(sizes["logs/syftboxd-4.log"], MAX_LOG_FILE_BYTES - 1024);
+        assert!(!sizes.contains_key("logs/syftboxd-5.log"));
+        assert!(!sizes.contains_key("logs/crashes/crash-1.txt"));
+        assert_eq!(sizes.values().sum::<u64>(), MAX_LOGS_BYTES);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
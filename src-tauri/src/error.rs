@@ -0,0 +1,111 @@
+//! Error returned by all Tauri commands
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Serialized as `{ code, message, details }`, where `code` is stable for the
+/// frontend to match on, `message` can be shown to the user and `details`
+/// carries the underlying error, if any.
+#[derive(Debug)]
+pub enum AppError {
+    /// The calling window may not use this command.
+    NotAllowed,
+    WindowNotFound(String),
+    /// The request was valid but the user declined it, e.g. in a dialog.
+    Cancelled,
+    /// The setting is locked by the system policy or could not be applied.
+    Settings(String),
+    Daemon(String),
+    Update(String),
+    OpenPath(String),
+    Window(String),
+    Diagnostics(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotAllowed => "NOT_ALLOWED",
+            AppError::WindowNotFound(_) => "WINDOW_NOT_FOUND",
+            AppError::Cancelled => "CANCELLED",
+            AppError::Settings(_) => "SETTINGS",
+            AppError::Daemon(_) => "DAEMON",
+            AppError::Update(_) => "UPDATE",
+            AppError::OpenPath(_) => "OPEN_PATH",
+            AppError::Window(_) => "WINDOW",
+            AppError::Diagnostics(_) => "DIAGNOSTICS",
+            AppError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            AppError::NotAllowed => "This window is not allowed to do that.".to_string(),
+            AppError::WindowNotFound(label) => format!("The {} window is not open.", label),
+            AppError::Cancelled => "Cancelled.".to_string(),
+            AppError::Settings(_) => "The settings could not be changed.".to_string(),
+            AppError::Daemon(_) => "The SyftBox daemon could not be reached.".to_string(),
+            AppError::Update(_) => "The update could not be installed.".to_string(),
+            AppError::OpenPath(_) => "This could not be opened.".to_string(),
+            AppError::Window(_) => "The window could not be updated.".to_string(),
+            AppError::Diagnostics(_) => "The diagnostics bundle could not be created.".to_string(),
+            AppError::Internal(_) => "Something went wrong, please check the logs.".to_string(),
+        }
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            AppError::NotAllowed | AppError::WindowNotFound(_) | AppError::Cancelled => None,
+            AppError::Settings(details)
+            | AppError::Daemon(details)
+            | AppError::Update(details)
+            | AppError::OpenPath(details)
+            | AppError::Window(details)
+            | AppError::Diagnostics(details)
+            | AppError::Internal(details) => Some(details),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.details() {
+            Some(details) => write!(f, "{} {}", self.message(), details),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Window(e.to_string())
+    }
+}
+
+/// Locking that recovers the data of a mutex poisoned by a panic on another
+/// thread, so that one failed task does not take every later command down.
+pub trait SafeLock<T> {
+    fn safe_lock(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> SafeLock<T> for Mutex<T> {
+    fn safe_lock(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(|e: PoisonError<_>| {
+            log::warn!("Recovering state from a poisoned lock");
+            e.into_inner()
+        })
+    }
+}
@@ -0,0 +1,195 @@
+//! Tray-only mode that runs the daemon without creating any webview
+
+use crate::state::UpdateWindowType;
+use crate::version::{DAEMON_VERSION, DESKTOP_VERSION};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+
+#[derive(Serialize)]
+struct DaemonAccess {
+    url: String,
+    token: String,
+}
+
+/// Decided once at startup, as the windows and daemon are set up for it and
+/// a changed setting only applies after a restart.
+pub struct HeadlessMode(bool);
+
+pub fn _detect_headless_mode(app: &AppHandle) -> HeadlessMode {
+    HeadlessMode(
+        app.state::<crate::cli::CliArgs>().headless
+            || crate::settings::_effective_settings(app).headless,
+    )
+}
+
+pub fn _is_headless(app: &AppHandle) -> bool {
+    app.state::<HeadlessMode>().0
+}
+
+pub fn _daemon_access_path(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::utils::_app_data_dir(app).map(|dir| dir.join("daemon_access.json"))
+}
+
+/// Writes to a temporary file that only the current user can read and moves
+/// it into place, so the token is never readable by others, even briefly.
+fn _write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no parent")
+    })?;
+    std::fs::create_dir_all(dir)?;
+    let temp_path = path.with_extension("tmp");
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp_path)?;
+    // The mode only applies to new files, not to one left by an earlier run
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, path)
+}
+
+/// Writes the daemon URL and token to a file only readable by the current
+/// user, so that the daemon can be used from a browser.
+pub fn _write_daemon_access_file(app: &AppHandle) {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    let access = DaemonAccess {
+        url: connection.base_url(),
+        token: connection.token.clone(),
+    };
+    let path = match _daemon_access_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("Failed to write daemon access file: {}", e);
+            return;
+        }
+    };
+
+    let result = serde_json::to_vec_pretty(&access)
+        .map_err(std::io::Error::from)
+        .and_then(|contents| _write_private_file(&path, &contents));
+    match result {
+        Ok(()) => {
+            log::info!(
+                "Daemon at {}, access details written to {}",
+                access.url,
+                path.display()
+            );
+            println!("SyftBox daemon: {}", access.url);
+            println!("Access token: see {}", path.display());
+        }
+        Err(e) => log::error!(
+            "Failed to write daemon access file {}: {}",
+            path.display(),
+            e
+        ),
+    }
+}
+
+/// Replaces the main window by opening the daemon in the default browser.
+pub fn _open_in_browser(app: &AppHandle) {
+    let url = crate::daemon_client::_get_daemon_connection(app).base_url();
+    log::debug!("Opening daemon at {} in the browser", url);
+    if let Err(e) = app.opener().open_url(&url, None::<&str>) {
+        log::error!("Failed to open {} in the browser: {}", url, e);
+    }
+}
+
+pub fn _show_about_dialog(app: &AppHandle) {
+    let access_path = _daemon_access_path(app)
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "the app data directory".to_string());
+    app.dialog()
+        .message(format!(
+            "SyftBox {}\nsyftboxd {}\n\nRunning in headless mode.\nDaemon access details are in {}",
+            DESKTOP_VERSION, DAEMON_VERSION, access_path
+        ))
+        .title("About SyftBox")
+        .show(|_| {});
+}
+
+/// Native dialogs in place of the update window.
+pub fn _show_update_dialog(
+    app: &AppHandle,
+    update_window_type: UpdateWindowType,
+    version: &str,
+    error: &str,
+) {
+    match update_window_type {
+        UpdateWindowType::Checking | UpdateWindowType::Downloading => {}
+        UpdateWindowType::None => {
+            app.dialog()
+                .message("SyftBox is up to date.")
+                .title("Updates")
+                .show(|_| {});
+        }
+        UpdateWindowType::Error | UpdateWindowType::Failed => {
+            app.dialog()
+                .message(error)
+                .kind(MessageDialogKind::Error)
+                .title("Updates")
+                .show(|_| {});
+        }
+        UpdateWindowType::Available => {
+            let app_handle = app.clone();
+            app.dialog()
+                .message(format!("SyftBox {} is available. Install it now?", version))
+                .title("Updates")
+                .buttons(MessageDialogButtons::OkCancelCustom(
+                    "Install".to_string(),
+                    "Later".to_string(),
+                ))
+                .show(move |install| {
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) =
+                            crate::commands::update_window_response(app_handle, install).await
+                        {
+                            log::error!("Failed to handle update response: {}", e);
+                        }
+                    });
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn private_file_is_only_readable_by_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("syftbox-headless-{}", std::process::id()));
+        let path = dir.join("daemon_access.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        // A temporary file left readable by an earlier run
+        std::fs::write(path.with_extension("tmp"), "old").unwrap();
+        std::fs::set_permissions(
+            path.with_extension("tmp"),
+            std::fs::Permissions::from_mode(0o644),
+        )
+        .unwrap();
+
+        _write_private_file(&path, b"secret").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read(&path).unwrap(), b"secret");
+        assert!(!path.with_extension("tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
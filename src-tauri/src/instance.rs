@@ -0,0 +1,107 @@
+//! Handling arguments forwarded by a second launch of the app
+
+use crate::cli::{CliArgs, CliCommand};
+use crate::error::SafeLock;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+/// Dispatches the arguments of a second instance to this one. The second
+/// process already validated them before exiting, see `cli::parse_args`.
+pub fn _handle_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    log::info!("Instance already running, handling forwarded arguments");
+    let cli_args = match crate::cli::parse_from(args) {
+        Ok(cli_args) => cli_args,
+        Err(e) => {
+            log::warn!("Ignoring invalid arguments from second instance: {}", e);
+            crate::utils::show_main_window(app);
+            return;
+        }
+    };
+    _warn_about_startup_only_args(&cli_args);
+
+    if let Some(profile) = &cli_args.profile {
+        _switch_profile(app, profile);
+    }
+
+    // Deep links are forwarded to the deep link plugin by the single instance
+    // plugin and routed from `deep_link::_setup_deep_links`
+    if let Some(CliCommand::Open { path }) = cli_args.command {
+        _open_path(app, Path::new(&cwd).join(path));
+    } else if cli_args.deep_links.is_empty() && !cli_args.hidden {
+        crate::utils::show_main_window(app);
+    }
+}
+
+fn _warn_about_startup_only_args(cli_args: &CliArgs) {
+    let ignored = [
+        ("--headless", cli_args.headless),
+        ("--daemon-url", cli_args.daemon_url.is_some()),
+        ("--log-level", cli_args.log_level.is_some()),
+        ("--no-update-check", cli_args.no_update_check),
+        ("--reset-settings", cli_args.reset_settings),
+    ];
+    for (name, _) in ignored.iter().filter(|(_, is_set)| *is_set) {
+        log::warn!("{} only applies when starting SyftBox, ignoring it", name);
+    }
+}
+
+fn _switch_profile(app: &AppHandle, profile: &str) {
+    let active_profile = app.state::<crate::state::ActiveProfile>();
+    {
+        let mut active = active_profile.0.safe_lock();
+        if active.as_deref() == Some(profile) {
+            return;
+        }
+        log::info!("Switching to profile {}", profile);
+        *active = Some(profile.to_string());
+    }
+
+    #[cfg(not(debug_assertions))]
+    {
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::daemon::_restart_daemon(&app_handle).await {
+                log::error!("Failed to restart the daemon with the new profile: {}", e);
+            }
+        });
+    }
+    #[cfg(debug_assertions)]
+    log::warn!("Switching profiles has no effect on the external daemon in debug mode");
+}
+
+/// Opens paths inside the workspace in the main window and reveals anything
+/// else in the OS file manager.
+fn _open_path(app: &AppHandle, path: PathBuf) {
+    let path = path.canonicalize().unwrap_or(path);
+    log::info!("Opening {} from second instance", path.display());
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let relative_path = match crate::daemon_client::_get_workspace_dir(&app_handle).await {
+            Ok(workspace_dir) => path
+                .strip_prefix(workspace_dir.canonicalize().unwrap_or(workspace_dir))
+                .ok()
+                .map(Path::to_path_buf),
+            Err(e) => {
+                log::warn!("Failed to get workspace directory: {}", e);
+                None
+            }
+        };
+
+        let Some(relative_path) = relative_path else {
+            if let Err(e) = app_handle.opener().reveal_item_in_dir(&path) {
+                log::error!("Failed to reveal {}: {}", path.display(), e);
+            }
+            return;
+        };
+        let link = format!(
+            "syft://workspace/{}",
+            relative_path
+                .components()
+                .map(|c| urlencoding::encode(&c.as_os_str().to_string_lossy()).into_owned())
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+        crate::deep_link::_open_deep_link(&app_handle, &link);
+    });
+}
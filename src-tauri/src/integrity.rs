@@ -0,0 +1,173 @@
+//! Integrity verification of the bundled sidecar binaries
+
+use crate::version::SIDECAR_HASHES;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+#[cfg(not(debug_assertions))]
+use {
+    crate::error::SafeLock,
+    std::collections::HashMap,
+    std::sync::Mutex,
+    std::time::SystemTime,
+    tauri::{AppHandle, Manager},
+    tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind},
+    tauri_plugin_opener::OpenerExt,
+};
+
+#[cfg(not(debug_assertions))]
+const REINSTALL_URL: &str = "https://syftbox.openmined.org";
+
+#[derive(Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IntegrityStatus {
+    Ok,
+    Missing,
+    Tampered,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarIntegrity {
+    pub name: String,
+    pub path: String,
+    pub expected_hash: String,
+    pub actual_hash: Option<String>,
+    pub status: IntegrityStatus,
+}
+
+/// Modification times of the sidecars that passed verification, so that they
+/// are only hashed again once they changed on disk.
+#[cfg(not(debug_assertions))]
+#[derive(Default)]
+pub struct VerifiedSidecars(Mutex<HashMap<PathBuf, SystemTime>>);
+
+fn _sidecar_path(name: &str) -> Result<PathBuf, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to get current executable path: {}", e))?;
+    let dir = exe.parent().ok_or("Failed to get executable directory")?;
+    Ok(dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+}
+
+pub fn _hash_file(path: &Path) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    Some(hex::encode(Sha256::digest(&data)))
+}
+
+pub fn _verify_sidecar(name: &str, expected_hash: &str) -> SidecarIntegrity {
+    let path = _sidecar_path(name).unwrap_or_else(|e| {
+        log::error!("{}", e);
+        PathBuf::from(name)
+    });
+    let actual_hash = _hash_file(&path);
+    let status = match &actual_hash {
+        None => IntegrityStatus::Missing,
+        Some(hash) if hash == expected_hash => IntegrityStatus::Ok,
+        Some(_) => IntegrityStatus::Tampered,
+    };
+    if status != IntegrityStatus::Ok {
+        log::error!(
+            "Sidecar {} failed integrity verification: expected {}, got {:?}",
+            path.display(),
+            expected_hash,
+            actual_hash
+        );
+    }
+    SidecarIntegrity {
+        name: name.to_string(),
+        path: path.display().to_string(),
+        expected_hash: expected_hash.to_string(),
+        actual_hash,
+        status,
+    }
+}
+
+#[cfg(not(debug_assertions))]
+fn _modified(path: &Path) -> Option<SystemTime> {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Verifies a single sidecar right before it is spawned, unless it passed
+/// verification and has not been modified since.
+#[cfg(not(debug_assertions))]
+pub fn _is_sidecar_intact(app: &AppHandle, name: &str) -> bool {
+    let Some((_, expected_hash)) = SIDECAR_HASHES.iter().find(|(sidecar, _)| *sidecar == name)
+    else {
+        log::error!("Sidecar {} is not in the integrity manifest", name);
+        return false;
+    };
+    let path = match _sidecar_path(name) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("{}", e);
+            return false;
+        }
+    };
+    let verified = app.state::<VerifiedSidecars>();
+    let modified = _modified(&path);
+    if modified.is_some() && verified.0.safe_lock().get(&path) == modified.as_ref() {
+        return true;
+    }
+    if _verify_sidecar(name, expected_hash).status != IntegrityStatus::Ok {
+        return false;
+    }
+    if let Some(modified) = modified {
+        verified.0.safe_lock().insert(path, modified);
+    }
+    true
+}
+
+pub fn _verify_all_sidecars() -> Vec<SidecarIntegrity> {
+    log::info!("Verifying integrity of {} sidecars", SIDECAR_HASHES.len());
+    SIDECAR_HASHES
+        .iter()
+        .map(|(name, expected_hash)| _verify_sidecar(name, expected_hash))
+        .collect()
+}
+
+/// Verifies all sidecars and, if any of them is missing or modified, offers
+/// to reinstall the app. Returns `false` if the sidecars must not be spawned.
+/// Hashing takes a while, so this must not run on the main thread.
+#[cfg(not(debug_assertions))]
+pub fn _ensure_sidecars_intact(app: &AppHandle) -> bool {
+    let mut failed = Vec::new();
+    for sidecar in _verify_all_sidecars() {
+        if sidecar.status != IntegrityStatus::Ok {
+            failed.push(sidecar.name);
+        } else if let Some(modified) = _modified(Path::new(&sidecar.path)) {
+            app.state::<VerifiedSidecars>()
+                .0
+                .safe_lock()
+                .insert(PathBuf::from(sidecar.path), modified);
+        }
+    }
+    if failed.is_empty() {
+        return true;
+    }
+
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "The following SyftBox components are missing or have been modified: {}.\n\nPlease reinstall SyftBox to repair the installation.",
+            failed.join(", ")
+        ))
+        .kind(MessageDialogKind::Error)
+        .title("SyftBox installation is damaged")
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Reinstall".to_string(),
+            "Quit".to_string(),
+        ))
+        .show(move |reinstall_selected| {
+            if reinstall_selected {
+                log::info!("User chose to reinstall, opening {}", REINSTALL_URL);
+                if let Err(e) = app_handle.opener().open_url(REINSTALL_URL, None::<&str>) {
+                    log::error!("Failed to open {}: {}", REINSTALL_URL, e);
+                }
+            }
+            app_handle.exit(1);
+        });
+    false
+}
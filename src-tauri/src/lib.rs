@@ -4,21 +4,51 @@ use std::sync::Mutex;
 use tauri::Manager;
 
 // Modules for application logic
+mod activity;
+pub mod cli;
 mod commands;
+pub mod crash;
+mod csp;
+#[cfg(not(debug_assertions))]
+mod daemon;
+mod daemon_client;
+mod daemon_proxy;
+#[cfg(not(debug_assertions))]
+mod daemon_socket;
+#[cfg(not(debug_assertions))]
+mod daemon_updates;
+mod deep_link;
+mod diagnostics;
+mod diagnostics_bundle;
+mod error;
+mod headless;
+mod instance;
+mod integrity;
+mod notifications;
+mod open_path;
+mod pause;
+mod policy;
+mod remote_access;
+mod settings;
+mod shortcut;
 mod state;
+mod tray;
 mod updates;
 mod utils;
 mod version;
+mod window_geometry;
 mod windows;
 
-pub fn run() {
+pub fn run(cli_args: cli::CliArgs) {
     log::info!("Starting SyftBox application");
+    let log_level = cli_args
+        .log_level
+        .map_or(log::LevelFilter::Info, |level| level.level_filter());
 
     tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            log::info!("Instance already running, showing main window");
-            utils::show_main_window(app);
-        }))
+        .plugin(tauri_plugin_single_instance::init(
+            instance::_handle_second_instance,
+        ))
         .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_os::init())
@@ -26,6 +56,13 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcut::_handle_global_shortcut)
+                .build(),
+        )
         .plugin(tauri_plugin_decorum::init())
         .plugin(
             tauri_plugin_log::Builder::default()
@@ -51,34 +88,69 @@ pub fn run() {
                 ))
                 .target(tauri_plugin_log::Target::new(
                     tauri_plugin_log::TargetKind::Folder {
-                        path: dirs::home_dir()
-                            .expect("Failed to get home directory")
-                            .join(".syftbox")
-                            .join("logs"),
+                        path: utils::_logs_dir(),
                         file_name: Some("SyftBoxDesktop".to_string()),
                     },
                 ))
-                .level(log::LevelFilter::Info)
+                .level(log_level)
                 .build(),
         )
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
-            None,
+            Some(vec![utils::HIDDEN_ARG]),
         ))
+        .register_asynchronous_uri_scheme_protocol(
+            daemon_proxy::PROXY_SCHEME,
+            daemon_proxy::_handle_proxy_request,
+        )
         .invoke_handler(tauri::generate_handler![
             commands::update_about_window_titlebar_color,
             commands::update_theme,
             commands::update_window_response,
             commands::get_window_state,
+            commands::get_diagnostics,
+            commands::get_settings,
+            commands::update_settings,
+            commands::set_autostart,
+            commands::take_pending_deep_links,
+            commands::get_daemon_credentials,
+            commands::rotate_daemon_token,
+            commands::open_path,
+            commands::take_crash_report,
+            commands::create_diagnostics_bundle,
+            commands::get_diagnostics_bundle,
         ])
-        .setup(|app| {
+        .setup(move |app| {
             log::info!("Setting up application");
+            if cli_args.reset_settings {
+                settings::_reset_settings(app.handle());
+            }
+            let external_daemon = cli_args.external_daemon();
+            app.manage(state::ActiveProfile(Mutex::new(cli_args.profile.clone())));
+            app.manage(cli_args.clone());
             // Initialize state
             app.manage(Mutex::new(state::AppState::default()));
             app.manage(state::PendingUpdate {
                 pending_update: Mutex::new(None),
                 pending_update_window_state: Mutex::new(None),
             });
+            app.manage(tray::TrayStatusState::default());
+            app.manage(tray::TrayController::default());
+            app.manage(pause::SyncPauseState::default());
+            app.manage(activity::RecentActivityState::default());
+            app.manage(notifications::NotificationState::default());
+            app.manage(shortcut::GlobalShortcutState::default());
+            app.manage(window_geometry::WindowGeometryState::default());
+            app.manage(deep_link::DeepLinkState::default());
+            app.manage(crash::CrashReportState::default());
+            let policy = policy::_load_policy();
+            let daemon_port_override = policy.daemon_port;
+            app.manage(policy);
+            app.manage(Mutex::new(settings::_load_settings(app.handle())));
+            app.manage(headless::_detect_headless_mode(app.handle()));
+            policy::_enforce_autostart_policy(app.handle());
+            settings::_refresh_autostart_entry(app.handle());
+            shortcut::_setup_global_shortcut(app.handle());
 
             // below block is recommended by tauri docs. See note at the bottom of this section
             // https://tauri.app/plugin/deep-linking/#registering-desktop-deep-links-at-runtime
@@ -89,32 +161,88 @@ pub fn run() {
             }
 
             // Generate daemon client arguments
-            let (daemon_host, daemon_port, daemon_token) = utils::_generate_daemon_client_args();
+            let is_external_daemon = external_daemon.is_some();
+            let (daemon_host, daemon_port, daemon_token) = external_daemon.unwrap_or_else(|| {
+                utils::_generate_daemon_client_args(
+                    daemon_port_override,
+                    settings::_effective_settings(app.handle()).allow_remote_access,
+                )
+            });
             log::debug!(
                 "Generated daemon connection args - host: {}, port: {}",
                 daemon_host,
                 daemon_port
             );
+            // A bundled daemon that supports it listens on a per-user socket
+            // unless it has to be reachable over the network
+            #[cfg(not(debug_assertions))]
+            let daemon_socket = (!is_external_daemon
+                && daemon_socket::_should_use_socket(app.handle(), daemon_port_override.is_some()))
+            .then(|| daemon_socket::_daemon_socket_path(app.handle()))
+            .and_then(|socket| {
+                socket
+                    .inspect_err(|e| {
+                        log::warn!("No daemon socket, using port {}: {}", daemon_port, e)
+                    })
+                    .ok()
+            });
+            #[cfg(debug_assertions)]
+            let daemon_socket = None;
+            app.manage(Mutex::new(state::DaemonConnection {
+                host: daemon_host.clone(),
+                port: daemon_port.clone(),
+                token: daemon_token.clone(),
+                external: is_external_daemon,
+                socket: daemon_socket,
+            }));
+            app.manage(csp::_generate_window_csps(app.handle()));
 
             // Setup sidecars for release builds
             #[cfg(not(debug_assertions))]
-            utils::_setup_sidecars_for_release_builds(
-                app.handle(),
-                &daemon_host,
-                &daemon_port,
-                &daemon_token,
-                utils::_is_app_updated(app.handle()),
-            );
+            {
+                app.manage(daemon::DaemonProcess::default());
+                app.manage(utils::ProcessWick::default());
+                app.manage(integrity::VerifiedSidecars::default());
+                if is_external_daemon {
+                    log::info!(
+                        "Using the external daemon at {}:{}",
+                        daemon_host,
+                        daemon_port
+                    );
+                } else {
+                    utils::_setup_sidecars_for_release_builds(
+                        app.handle(),
+                        &daemon_port,
+                        utils::_is_app_updated(app.handle()),
+                    );
+                }
+            }
+
+            // Generate main URL and setup main window, unless started hidden in
+            // which case it is created on first `show_main_window`
+            if headless::_is_headless(app.handle()) {
+                log::info!("Running headless, no windows will be created");
+                headless::_write_daemon_access_file(app.handle());
+                #[cfg(target_os = "macos")]
+                app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+            } else if utils::_is_started_hidden(app.handle()) {
+                log::info!("Started hidden, only the tray and daemon are running");
+                #[cfg(target_os = "macos")]
+                app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+            } else {
+                windows::_setup_main_window(app.handle(), utils::_generate_main_url());
+            }
 
-            // Generate main URL and setup main window
-            let url = utils::_generate_main_url(&daemon_host, &daemon_port, &daemon_token);
-            windows::_setup_main_window(app.handle(), url);
+            deep_link::_setup_deep_links(app.handle());
 
             // Start periodic update checks
             updates::_start_periodic_update_checks(app.handle());
 
             // Setup system tray
             utils::_setup_system_tray(app.handle());
+            tray::_start_status_polling(app.handle());
+            activity::_start_activity_subscription(app.handle());
+            crash::_offer_crash_report(app.handle());
 
             log::info!("Application setup completed");
             Ok(())
@@ -124,12 +252,7 @@ pub fn run() {
                 if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                     log::info!("Main window close requested - hiding window");
                     api.prevent_close();
-                    window.hide().unwrap();
-                    window.set_skip_taskbar(true).unwrap();
-                    #[cfg(target_os = "macos")]
-                    let _ = window
-                        .app_handle()
-                        .set_activation_policy(tauri::ActivationPolicy::Accessory);
+                    utils::hide_main_window(window.app_handle());
                 }
             }
         })
@@ -138,6 +261,7 @@ pub fn run() {
         .run(move |_app_handle, event| match event {
             tauri::RunEvent::Exit => {
                 log::info!("Exiting application");
+                window_geometry::_save_window_geometry(_app_handle);
             }
             #[cfg(target_os = "macos")]
             tauri::RunEvent::Reopen {
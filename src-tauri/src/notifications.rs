@@ -0,0 +1,213 @@
+//! Native desktop notifications for background activity
+
+use crate::error::SafeLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Minimum time between two notifications of the same category.
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NotificationCategory {
+    UpdateAvailable,
+    DaemonStatus,
+    SyncConflict,
+    IncomingShare,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    pub update_available: bool,
+    pub daemon_status: bool,
+    pub sync_conflict: bool,
+    pub incoming_share: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            update_available: true,
+            daemon_status: true,
+            sync_conflict: true,
+            incoming_share: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    fn is_enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::UpdateAvailable => self.update_available,
+            NotificationCategory::DaemonStatus => self.daemon_status,
+            NotificationCategory::SyncConflict => self.sync_conflict,
+            NotificationCategory::IncomingShare => self.incoming_share,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct NotificationState {
+    last_sent: Mutex<HashMap<NotificationCategory, Instant>>,
+}
+
+/// What clicking a notification opens.
+#[derive(Clone, Debug)]
+pub enum NotificationTarget {
+    MainWindow,
+    /// Label of a window that is already open, such as the update window
+    Window(&'static str),
+    /// `syft://` link routed to the main window
+    DeepLink(String),
+}
+
+fn _open_target(app: &AppHandle, target: &NotificationTarget) {
+    log::debug!("Notification clicked, opening {:?}", target);
+    match target {
+        NotificationTarget::MainWindow => {
+            if !crate::headless::_is_headless(app) {
+                crate::utils::show_main_window(app);
+            }
+        }
+        NotificationTarget::Window(label) => {
+            if let Some(window) = app.get_webview_window(label) {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        NotificationTarget::DeepLink(link) => crate::deep_link::_open_deep_link(app, link),
+    }
+}
+
+/// Sends the notification and waits on the calling thread until it is
+/// clicked or closed. The notification plugin does not report clicks on
+/// desktop platforms, so the platform APIs it wraps are used directly.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn _show_notification(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    target: &NotificationTarget,
+) -> Result<(), String> {
+    let handle = notify_rust::Notification::new()
+        .summary(title)
+        .body(body)
+        .auto_icon()
+        // Servers invoke the "default" action when the notification is clicked
+        .action("default", "Open")
+        .show()
+        .map_err(|e| e.to_string())?;
+    handle.wait_for_action(|action| {
+        if action == "default" {
+            _open_target(app, target);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn _show_notification(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    target: &NotificationTarget,
+) -> Result<(), String> {
+    use mac_notification_sys::{Notification, NotificationResponse};
+
+    // Fails once already set, notifications of dev builds are sent as the
+    // terminal as the app bundle is not registered
+    let _ = mac_notification_sys::set_application(if tauri::is_dev() {
+        "com.apple.Terminal"
+    } else {
+        &app.config().identifier
+    });
+    let response = Notification::new()
+        .title(title)
+        .message(body)
+        .wait_for_click(true)
+        .send()
+        .map_err(|e| e.to_string())?;
+    if matches!(response, NotificationResponse::Click) {
+        _open_target(app, target);
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn _show_notification(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    target: &NotificationTarget,
+) -> Result<(), String> {
+    use tauri_winrt_notification::Toast;
+
+    // Toasts are only shown for the app's ID once it is installed, builds run
+    // from the target directory borrow the ID of PowerShell instead
+    let exe_dir = tauri::utils::platform::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()));
+    let is_installed = exe_dir.is_some_and(|dir| {
+        !dir.ends_with(std::path::Path::new("target").join("debug"))
+            && !dir.ends_with(std::path::Path::new("target").join("release"))
+    });
+    let app_id = if is_installed {
+        app.config().identifier.clone()
+    } else {
+        Toast::POWERSHELL_APP_ID.to_string()
+    };
+
+    let app_handle = app.clone();
+    let target = target.clone();
+    Toast::new(&app_id)
+        .title(title)
+        .text1(body)
+        .on_activated(move |_| {
+            _open_target(&app_handle, &target);
+            Ok(())
+        })
+        .show()
+        .map_err(|e| e.to_string())
+}
+
+/// Shows a notification that opens `target` when clicked.
+pub fn _notify(
+    app: &AppHandle,
+    category: NotificationCategory,
+    title: &str,
+    body: &str,
+    target: NotificationTarget,
+) {
+    let settings = crate::settings::_effective_settings(app);
+    if !settings.notifications.is_enabled(category) {
+        log::debug!("Notifications for {:?} are disabled", category);
+        return;
+    }
+
+    let state = app.state::<NotificationState>();
+    {
+        let mut last_sent = state.last_sent.safe_lock();
+        if last_sent
+            .get(&category)
+            .is_some_and(|sent| sent.elapsed() < RATE_LIMIT_INTERVAL)
+        {
+            log::debug!("Rate limiting {:?} notification: {}", category, title);
+            return;
+        }
+        last_sent.insert(category, Instant::now());
+    }
+
+    log::info!("Showing {:?} notification: {}", category, title);
+    let app_handle = app.clone();
+    let title = title.to_string();
+    let body = body.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = _show_notification(&app_handle, &title, &body, &target) {
+            log::error!("Failed to show notification: {}", e);
+        }
+    });
+}
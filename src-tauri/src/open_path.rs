@@ -0,0 +1,159 @@
+//! Opening paths and URLs requested by the webviews
+
+use crate::error::AppError;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Url};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+
+/// Hosts the main window opens without asking, any other URL needs a
+/// confirmation. Local servers are included in that, as any app can run one.
+const ALLOWED_URL_HOSTS: [&str; 4] = [
+    "openmined.org",
+    "syftbox.openmined.org",
+    "github.com",
+    "raw.githubusercontent.com",
+];
+
+/// The only pages the about window links to.
+const ABOUT_URLS: [&str; 3] = [
+    "https://syftbox.openmined.org/",
+    "https://raw.githubusercontent.com/OpenMined/syft/refs/heads/main/LICENSE",
+    "https://openmined.org/privacy-policy/",
+];
+
+/// Release notes shown in the updates window link to their release.
+const UPDATES_URL_PREFIX: &str = "https://github.com/OpenMined/SyftUI/releases/tag/";
+
+#[cfg(windows)]
+const EXECUTABLE_EXTENSIONS: [&str; 19] = [
+    "exe", "bat", "cmd", "com", "msi", "ps1", "vbs", "vbe", "js", "jse", "wsf", "lnk", "scr",
+    "hta", "reg", "jar", "cpl", "msc", "pif",
+];
+#[cfg(target_os = "macos")]
+const EXECUTABLE_EXTENSIONS: [&str; 5] = ["app", "command", "pkg", "sh", "terminal"];
+#[cfg(all(unix, not(target_os = "macos")))]
+const EXECUTABLE_EXTENSIONS: [&str; 3] = ["desktop", "sh", "run"];
+
+fn _is_executable(path: &Path) -> bool {
+    let has_executable_extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            EXECUTABLE_EXTENSIONS
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(extension))
+        });
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let has_executable_mode = path
+            .metadata()
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0);
+        has_executable_extension || has_executable_mode
+    }
+    #[cfg(not(unix))]
+    has_executable_extension
+}
+
+fn _is_allowed_url(url: &Url) -> bool {
+    url.host_str().is_some_and(|host| {
+        ALLOWED_URL_HOSTS
+            .iter()
+            .any(|allowed| host.eq_ignore_ascii_case(allowed))
+    })
+}
+
+/// Directories the main window may open paths in.
+async fn _allowed_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = vec![crate::utils::_logs_dir()];
+    match crate::daemon_client::_get_workspace_dir(app).await {
+        Ok(workspace_dir) => dirs.push(workspace_dir),
+        Err(e) => log::debug!("Workspace is not available to open paths in: {}", e),
+    }
+    dirs.into_iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect()
+}
+
+async fn _confirm(app: &AppHandle, message: String) -> bool {
+    let dialog = app
+        .dialog()
+        .message(message)
+        .kind(MessageDialogKind::Warning)
+        .title("Open")
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Open".to_string(),
+            "Cancel".to_string(),
+        ));
+    tauri::async_runtime::spawn_blocking(move || dialog.blocking_show())
+        .await
+        .unwrap_or(false)
+}
+
+async fn _open_url(app: &AppHandle, label: &str, url: Url) -> Result<(), AppError> {
+    match label {
+        "main" => {
+            if !_is_allowed_url(&url)
+                && !_confirm(app, format!("Open {} in your browser?", url.as_str())).await
+            {
+                return Err(AppError::Cancelled);
+            }
+        }
+        "about" if ABOUT_URLS.contains(&url.as_str()) => {}
+        "updates" if url.as_str().starts_with(UPDATES_URL_PREFIX) => {}
+        _ => {
+            log::warn!("Refused to open {} from window {}", url, label);
+            return Err(AppError::NotAllowed);
+        }
+    }
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
+        .map_err(|e| AppError::OpenPath(format!("Failed to open {}: {}", url, e)))
+}
+
+async fn _open_local_path(app: &AppHandle, path: &str) -> Result<(), AppError> {
+    let path = Path::new(path)
+        .canonicalize()
+        .map_err(|e| AppError::OpenPath(format!("Failed to open {}: {}", path, e)))?;
+    if !_allowed_dirs(app)
+        .await
+        .iter()
+        .any(|dir| path.starts_with(dir))
+    {
+        log::warn!("Refused to open {} outside the workspace", path.display());
+        return Err(AppError::OpenPath(
+            "Only files in the workspace or logs can be opened".to_string(),
+        ));
+    }
+    if _is_executable(&path)
+        && !_confirm(
+            app,
+            format!(
+                "{} is a program that will run on your computer. Only open it if you trust where it came from.",
+                path.display()
+            ),
+        )
+        .await
+    {
+        return Err(AppError::Cancelled);
+    }
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| AppError::OpenPath(format!("Failed to open {}: {}", path.display(), e)))
+}
+
+/// Opens a URL the window may link to in the browser or, for the main window
+/// only, a file or folder from the workspace or logs in its default app.
+pub async fn _open_path(app: &AppHandle, label: &str, path: &str) -> Result<(), AppError> {
+    match Url::parse(path) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => _open_url(app, label, url).await,
+        // Single letters are Windows drive letters
+        Ok(url) if url.scheme().len() > 1 => Err(AppError::OpenPath(format!(
+            "Cannot open {} URLs",
+            url.scheme()
+        ))),
+        _ if label == "main" => _open_local_path(app, path).await,
+        _ => Err(AppError::NotAllowed),
+    }
+}
@@ -0,0 +1,196 @@
+//! Temporarily pausing sync by stopping the daemon
+
+use crate::error::SafeLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PauseOption {
+    ThirtyMinutes,
+    OneHour,
+    UntilRestart,
+}
+
+impl PauseOption {
+    pub const ALL: [PauseOption; 3] = [
+        PauseOption::ThirtyMinutes,
+        PauseOption::OneHour,
+        PauseOption::UntilRestart,
+    ];
+
+    pub fn menu_id(&self) -> &'static str {
+        match self {
+            PauseOption::ThirtyMinutes => "pause_sync_30m",
+            PauseOption::OneHour => "pause_sync_1h",
+            PauseOption::UntilRestart => "pause_sync_until_restart",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseOption::ThirtyMinutes => "For 30 Minutes",
+            PauseOption::OneHour => "For 1 Hour",
+            PauseOption::UntilRestart => "Until Restart",
+        }
+    }
+
+    pub fn from_menu_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|option| option.menu_id() == id)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        match self {
+            PauseOption::ThirtyMinutes => Some(Duration::from_secs(30 * 60)),
+            PauseOption::OneHour => Some(Duration::from_secs(60 * 60)),
+            PauseOption::UntilRestart => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPause {
+    pub option: PauseOption,
+    /// Unix timestamp in seconds, `None` when paused until restart.
+    pub paused_until: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct SyncPauseState {
+    pub pause: Mutex<Option<SyncPause>>,
+    /// Incremented on every pause or resume so that a stale resume timer does
+    /// not resume a newer pause.
+    pub generation: AtomicU64,
+}
+
+fn _sync_pause_path(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::utils::_app_data_dir(app).map(|dir| dir.join("sync_pause.json"))
+}
+
+fn _now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+pub fn _get_sync_pause(app: &AppHandle) -> Option<SyncPause> {
+    *app.state::<SyncPauseState>().pause.safe_lock()
+}
+
+pub fn _is_sync_paused(app: &AppHandle) -> bool {
+    _get_sync_pause(app).is_some()
+}
+
+/// Only timed pauses are persisted, a pause until restart ends with the app.
+fn _persist_sync_pause(app: &AppHandle, pause: Option<SyncPause>) {
+    let path = match _sync_pause_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("Failed to persist sync pause state: {}", e);
+            return;
+        }
+    };
+    let result = match pause.filter(|pause| pause.paused_until.is_some()) {
+        Some(pause) => serde_json::to_string(&pause)
+            .map_err(std::io::Error::from)
+            .and_then(|content| std::fs::write(&path, content)),
+        None => std::fs::remove_file(&path).or_else(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Ok(()),
+            _ => Err(e),
+        }),
+    };
+    if let Err(e) = result {
+        log::error!(
+            "Failed to persist sync pause state to {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+fn _set_sync_pause(app: &AppHandle, pause: Option<SyncPause>) {
+    let state = app.state::<SyncPauseState>();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    *state.pause.safe_lock() = pause;
+    _persist_sync_pause(app, pause);
+    crate::tray::_set_sync_pause(app, pause);
+
+    let Some(paused_until) = pause.and_then(|pause| pause.paused_until) else {
+        return;
+    };
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(paused_until.saturating_sub(_now()))).await;
+        let state = app_handle.state::<SyncPauseState>();
+        if state.generation.load(Ordering::SeqCst) == generation {
+            log::info!("Sync pause expired");
+            _resume_sync(&app_handle);
+        }
+    });
+}
+
+pub fn _pause_sync(app: &AppHandle, option: PauseOption) {
+    log::info!("Pausing sync: {:?}", option);
+    let was_paused = _is_sync_paused(app);
+    _set_sync_pause(
+        app,
+        Some(SyncPause {
+            option,
+            paused_until: option
+                .duration()
+                .map(|duration| _now() + duration.as_secs()),
+        }),
+    );
+
+    if !was_paused {
+        #[cfg(not(debug_assertions))]
+        crate::daemon::_stop_daemon(app);
+        #[cfg(debug_assertions)]
+        log::warn!("Pausing sync has no effect on the external daemon in debug mode");
+    }
+}
+
+pub fn _resume_sync(app: &AppHandle) {
+    if !_is_sync_paused(app) {
+        return;
+    }
+    log::info!("Resuming sync");
+    _set_sync_pause(app, None);
+
+    #[cfg(not(debug_assertions))]
+    {
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::daemon::_restart_daemon(&app_handle).await {
+                log::error!("Failed to resume sync: {}", e);
+            }
+        });
+    }
+}
+
+/// Restores a timed pause from a previous session that is still in effect.
+/// Returns `true` if sync should stay paused and the daemon must not start.
+pub fn _restore_sync_pause(app: &AppHandle) -> bool {
+    let pause = _sync_pause_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<SyncPause>(&content).ok());
+    match pause {
+        Some(pause) if pause.paused_until.is_some_and(|until| until > _now()) => {
+            log::info!("Restoring sync pause from a previous session: {:?}", pause);
+            _set_sync_pause(app, Some(pause));
+            true
+        }
+        Some(_) => {
+            _persist_sync_pause(app, None);
+            false
+        }
+        None => false,
+    }
+}
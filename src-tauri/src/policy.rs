@@ -0,0 +1,226 @@
+//! System-wide policy file for centrally managed installations
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+
+const UPDATE_ENDPOINT_BASE: &str = "https://github.com/OpenMined/SyftUI/releases";
+/// Channels that releases are published to, anything else is rejected.
+pub const UPDATE_CHANNELS: [&str; 2] = ["stable", "beta"];
+
+pub fn _validate_update_channel(channel: &str) -> Result<(), String> {
+    if UPDATE_CHANNELS.contains(&channel) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown update channel {:?}, expected one of {:?}",
+            channel, UPDATE_CHANNELS
+        ))
+    }
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Policy {
+    pub disable_auto_update: bool,
+    pub update_channel: Option<String>,
+    pub autostart: Option<bool>,
+    pub daemon_port: Option<u16>,
+    pub disable_bug_report_upload: bool,
+}
+
+impl Policy {
+    /// Names of the settings that are locked by this policy, matching the
+    /// field names used by the settings commands.
+    pub fn locked_settings(&self) -> Vec<String> {
+        let mut locked = Vec::new();
+        if self.disable_auto_update {
+            locked.push("autoUpdateCheck".to_string());
+        }
+        if self.update_channel.is_some() {
+            locked.push("updateChannel".to_string());
+        }
+        if self.autostart.is_some() {
+            locked.push("autostart".to_string());
+        }
+        if self.daemon_port.is_some() {
+            locked.push("daemonPort".to_string());
+        }
+        if self.disable_bug_report_upload {
+            locked.push("bugReportUpload".to_string());
+        }
+        locked
+    }
+}
+
+pub fn _policy_path() -> PathBuf {
+    #[cfg(target_os = "linux")]
+    {
+        PathBuf::from("/etc/syftbox/policy.json")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/SyftBox/policy.json")
+    }
+    #[cfg(windows)]
+    {
+        PathBuf::from(
+            std::env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string()),
+        )
+        .join("SyftBox")
+        .join("policy.json")
+    }
+}
+
+/// Drops an update channel that releases are not published to.
+fn _parse_policy(content: &str) -> Result<Policy, serde_json::Error> {
+    let mut policy = serde_json::from_str::<Policy>(content)?;
+    if let Some(channel) = &policy.update_channel {
+        if let Err(e) = _validate_update_channel(channel) {
+            log::error!("Ignoring update channel in policy file: {}", e);
+            policy.update_channel = None;
+        }
+    }
+    Ok(policy)
+}
+
+pub fn _load_policy() -> Policy {
+    let path = _policy_path();
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::error!("Failed to read policy file {}: {}", path.display(), e);
+            }
+            return Policy::default();
+        }
+    };
+    match _parse_policy(&content) {
+        Ok(policy) => {
+            log::info!(
+                "Loaded policy from {} (locked settings: {:?})",
+                path.display(),
+                policy.locked_settings()
+            );
+            policy
+        }
+        Err(e) => {
+            log::error!("Failed to parse policy file {}: {}", path.display(), e);
+            Policy::default()
+        }
+    }
+}
+
+pub fn _update_endpoint_for_channel(channel: &str) -> Result<String, String> {
+    _validate_update_channel(channel)?;
+    Ok(match channel {
+        "stable" => format!("{}/latest/download/release.json", UPDATE_ENDPOINT_BASE),
+        channel => format!("{}/download/{}/release.json", UPDATE_ENDPOINT_BASE, channel),
+    })
+}
+
+/// Daemon releases are published alongside the desktop releases of the same
+/// channel.
+pub fn _daemon_update_endpoint_for_channel(channel: &str) -> Result<String, String> {
+    _validate_update_channel(channel)?;
+    Ok(match channel {
+        "stable" => format!(
+            "{}/latest/download/daemon-release.json",
+            UPDATE_ENDPOINT_BASE
+        ),
+        channel => format!(
+            "{}/download/{}/daemon-release.json",
+            UPDATE_ENDPOINT_BASE, channel
+        ),
+    })
+}
+
+pub fn _enforce_autostart_policy(app: &AppHandle) {
+    let Some(autostart) = app.state::<Policy>().autostart else {
+        return;
+    };
+    let manager = app.autolaunch();
+    if manager.is_enabled().unwrap_or(false) == autostart {
+        return;
+    }
+    log::info!("Enforcing autostart policy: {}", autostart);
+    let result = if autostart {
+        manager.enable()
+    } else {
+        manager.disable()
+    };
+    if let Err(e) = result {
+        log::error!("Failed to enforce autostart policy: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_policy_files() {
+        let policy = _parse_policy(
+            r#"{"disableAutoUpdate": true, "updateChannel": "beta", "daemonPort": 7938}"#,
+        )
+        .unwrap();
+        assert!(policy.disable_auto_update);
+        assert_eq!(policy.update_channel.as_deref(), Some("beta"));
+        assert_eq!(policy.daemon_port, Some(7938));
+        assert_eq!(policy.autostart, None);
+        assert!(!policy.disable_bug_report_upload);
+
+        let policy = _parse_policy("{}").unwrap();
+        assert!(policy.locked_settings().is_empty());
+    }
+
+    #[test]
+    fn drops_unknown_update_channels() {
+        let policy = _parse_policy(r#"{"updateChannel": "nightly"}"#).unwrap();
+        assert_eq!(policy.update_channel, None);
+        assert!(policy.locked_settings().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_policy_files() {
+        assert!(_parse_policy("").is_err());
+        assert!(_parse_policy(r#"{"daemonPort": 70000}"#).is_err());
+        assert!(_parse_policy(r#"{"autostart": "yes"}"#).is_err());
+    }
+
+    #[test]
+    fn lists_locked_settings() {
+        let policy = Policy {
+            disable_auto_update: true,
+            update_channel: Some("stable".to_string()),
+            autostart: Some(false),
+            daemon_port: Some(7938),
+            disable_bug_report_upload: true,
+        };
+        assert_eq!(
+            policy.locked_settings(),
+            [
+                "autoUpdateCheck",
+                "updateChannel",
+                "autostart",
+                "daemonPort",
+                "bugReportUpload"
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_update_endpoints_for_known_channels() {
+        assert_eq!(
+            _update_endpoint_for_channel("stable").unwrap(),
+            "https://github.com/OpenMined/SyftUI/releases/latest/download/release.json"
+        );
+        assert_eq!(
+            _daemon_update_endpoint_for_channel("beta").unwrap(),
+            "https://github.com/OpenMined/SyftUI/releases/download/beta/daemon-release.json"
+        );
+        assert!(_update_endpoint_for_channel("../stable").is_err());
+        assert!(_daemon_update_endpoint_for_channel("").is_err());
+    }
+}
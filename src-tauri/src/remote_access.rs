@@ -0,0 +1,40 @@
+//! Opt-in for binding the daemon to a non-loopback address
+
+use std::net::IpAddr;
+use tauri::AppHandle;
+
+pub const LOOPBACK_HOST: &str = "127.0.0.1";
+
+pub fn _is_loopback_host(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_matches(|c| c == '[' || c == ']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Returns the address the daemon may bind to, falling back to loopback when
+/// `host` would expose it to the network without the user's consent.
+pub fn _validate_bind_host(host: String, allow_remote_access: bool) -> String {
+    if _is_loopback_host(&host) {
+        return host;
+    }
+    if !allow_remote_access {
+        log::error!(
+            "Refusing to expose the daemon on {}, enable allowRemoteAccess in the settings to allow it",
+            host
+        );
+        return LOOPBACK_HOST.to_string();
+    }
+    log::warn!(
+        "Remote access is enabled, the daemon is reachable on {}",
+        host
+    );
+    host
+}
+
+/// Whether the daemon we started is reachable from other machines.
+pub fn _is_remote_access_active(app: &AppHandle) -> bool {
+    let connection = crate::daemon_client::_get_daemon_connection(app);
+    !connection.external && !_is_loopback_host(&connection.host)
+}
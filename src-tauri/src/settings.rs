@@ -0,0 +1,249 @@
+//! Persistent desktop settings
+
+use crate::error::SafeLock;
+use crate::notifications::NotificationSettings;
+use crate::policy::Policy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub auto_update_check: bool,
+    pub update_channel: String,
+    pub bug_report_upload: bool,
+    pub notifications: NotificationSettings,
+    /// Accelerator that toggles the main window, `None` when disabled. Off by
+    /// default so that no shortcut is taken from other apps without asking.
+    pub global_shortcut: Option<String>,
+    /// Never create a webview, applied on the next launch.
+    pub headless: bool,
+    /// Allow a non-loopback `DAEMON_HOST`, applied on the next launch.
+    pub allow_remote_access: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_update_check: true,
+            update_channel: "stable".to_string(),
+            bug_report_upload: true,
+            notifications: NotificationSettings::default(),
+            global_shortcut: None,
+            headless: false,
+            allow_remote_access: false,
+        }
+    }
+}
+
+/// Settings as exposed to the frontend, including values owned by the OS or
+/// the daemon and the list of settings locked by the system policy.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsState {
+    pub settings: Settings,
+    pub autostart: bool,
+    pub daemon_port: String,
+    /// Whether the running daemon is reachable from the network.
+    pub remote_access: bool,
+    pub locked: Vec<String>,
+}
+
+fn _settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::utils::_app_data_dir(app).map(|dir| dir.join("settings.json"))
+}
+
+pub fn _load_settings(app: &AppHandle) -> Settings {
+    let path = match _settings_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("Failed to load settings: {}", e);
+            return Settings::default();
+        }
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            log::error!("Failed to parse settings {}: {}", path.display(), e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+pub fn _save_settings(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = _settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write settings {}: {}", path.display(), e))
+}
+
+pub fn _reset_settings(app: &AppHandle) {
+    let path = match _settings_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("Failed to reset settings: {}", e);
+            return;
+        }
+    };
+    log::info!("Resetting settings at {}", path.display());
+    if let Err(e) = std::fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::error!("Failed to remove settings {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Returns the settings with the system policy applied on top.
+pub fn _effective_settings(app: &AppHandle) -> Settings {
+    let mut settings = app.state::<Mutex<Settings>>().safe_lock().clone();
+    let policy = app.state::<Policy>();
+    if policy.disable_auto_update {
+        settings.auto_update_check = false;
+    }
+    if let Some(channel) = &policy.update_channel {
+        settings.update_channel = channel.clone();
+    }
+    if policy.disable_bug_report_upload {
+        settings.bug_report_upload = false;
+    }
+    settings
+}
+
+pub fn _get_settings_state(app: &AppHandle) -> SettingsState {
+    SettingsState {
+        settings: _effective_settings(app),
+        autostart: app.autolaunch().is_enabled().unwrap_or(false),
+        daemon_port: app
+            .state::<Mutex<crate::state::DaemonConnection>>()
+            .safe_lock()
+            .port
+            .clone(),
+        remote_access: crate::remote_access::_is_remote_access_active(app),
+        locked: app.state::<Policy>().locked_settings(),
+    }
+}
+
+pub fn _update_settings(app: &AppHandle, new_settings: Settings) -> Result<(), String> {
+    let current = _effective_settings(app);
+    let locked = app.state::<Policy>().locked_settings();
+    let is_locked = |name: &str| locked.iter().any(|l| l == name);
+    if (is_locked("autoUpdateCheck") && new_settings.auto_update_check != current.auto_update_check)
+        || (is_locked("updateChannel") && new_settings.update_channel != current.update_channel)
+        || (is_locked("bugReportUpload")
+            && new_settings.bug_report_upload != current.bug_report_upload)
+    {
+        return Err("This setting is managed by your organization".to_string());
+    }
+
+    if new_settings.update_channel != current.update_channel {
+        crate::policy::_validate_update_channel(&new_settings.update_channel)?;
+    }
+
+    let is_shortcut_changed = new_settings.global_shortcut != current.global_shortcut;
+    if is_shortcut_changed {
+        crate::shortcut::_set_global_shortcut(app, new_settings.global_shortcut.as_deref())?;
+    }
+
+    if let Err(e) = _save_settings(app, &new_settings) {
+        // Keep the registered shortcut in line with the saved settings
+        if is_shortcut_changed {
+            if let Err(e) =
+                crate::shortcut::_set_global_shortcut(app, current.global_shortcut.as_deref())
+            {
+                log::error!("Failed to restore global shortcut: {}", e);
+            }
+        }
+        return Err(e);
+    }
+    *app.state::<Mutex<Settings>>().safe_lock() = new_settings;
+    Ok(())
+}
+
+pub fn _set_autostart(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let result = if app.state::<Policy>().autostart.is_some() {
+        Err("Autostart is managed by your organization".to_string())
+    } else if enabled {
+        app.autolaunch()
+            .enable()
+            .map_err(|e| format!("Failed to enable autostart: {}", e))
+    } else {
+        app.autolaunch()
+            .disable()
+            .map_err(|e| format!("Failed to disable autostart: {}", e))
+    };
+    // The tray checkmark may have been toggled by the click that got us here
+    crate::tray::_refresh_tray_menu(app);
+    result
+}
+
+/// Rewrites an existing autostart entry so that entries created by older
+/// versions also pass the current launch arguments.
+pub fn _refresh_autostart_entry(app: &AppHandle) {
+    if app.state::<Policy>().autostart.is_some() || !app.autolaunch().is_enabled().unwrap_or(false)
+    {
+        return;
+    }
+    if let Err(e) = app.autolaunch().enable() {
+        log::error!("Failed to refresh autostart entry: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_settings_use_the_defaults() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(settings.auto_update_check);
+        assert_eq!(settings.update_channel, "stable");
+        assert!(settings.bug_report_upload);
+        assert!(settings.notifications.sync_conflict);
+        assert_eq!(settings.global_shortcut, None);
+        assert!(!settings.headless);
+        assert!(!settings.allow_remote_access);
+    }
+
+    #[test]
+    fn parses_settings_from_older_versions() {
+        let settings: Settings = serde_json::from_str(
+            r#"{"autoUpdateCheck": false, "notifications": {"daemonStatus": false}, "removedSetting": 1}"#,
+        )
+        .unwrap();
+        assert!(!settings.auto_update_check);
+        assert!(!settings.notifications.daemon_status);
+        assert!(settings.notifications.update_available);
+        assert_eq!(settings.update_channel, "stable");
+    }
+
+    #[test]
+    fn round_trips_settings() {
+        let settings = Settings {
+            update_channel: "beta".to_string(),
+            global_shortcut: Some("CmdOrCtrl+Shift+S".to_string()),
+            headless: true,
+            ..Settings::default()
+        };
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["updateChannel"], "beta");
+        assert_eq!(json["globalShortcut"], "CmdOrCtrl+Shift+S");
+        let parsed: Settings = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.update_channel, "beta");
+        assert_eq!(parsed.global_shortcut, settings.global_shortcut);
+        assert!(parsed.headless);
+    }
+
+    #[test]
+    fn rejects_settings_of_the_wrong_type() {
+        assert!(serde_json::from_str::<Settings>(r#"{"headless": "yes"}"#).is_err());
+        assert!(serde_json::from_str::<Settings>(r#""settings""#).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Global keyboard shortcut that toggles the main window
+
+use crate::error::SafeLock;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+/// The shortcut currently registered with the OS, if any.
+#[derive(Default)]
+pub struct GlobalShortcutState(pub Mutex<Option<Shortcut>>);
+
+pub fn _handle_global_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    log::debug!("Global shortcut {:?} pressed", shortcut);
+    _toggle_main_window(app);
+}
+
+fn _toggle_main_window(app: &AppHandle) {
+    let is_active = app.get_webview_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false)
+    });
+    if is_active {
+        crate::utils::hide_main_window(app);
+    } else {
+        crate::utils::show_main_window(app);
+    }
+}
+
+fn _parse_shortcut(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator)
+        .map_err(|e| format!("Invalid keyboard shortcut \"{}\": {}", accelerator, e))
+}
+
+/// Replaces the registered global shortcut, `None` disables it. If the new
+/// shortcut is taken by another application the previous one stays registered.
+pub fn _set_global_shortcut(app: &AppHandle, accelerator: Option<&str>) -> Result<(), String> {
+    let shortcut = accelerator.map(_parse_shortcut).transpose()?;
+    let state = app.state::<GlobalShortcutState>();
+    let mut registered = state.0.safe_lock();
+    if *registered == shortcut {
+        return Ok(());
+    }
+
+    let global_shortcut = app.global_shortcut();
+    let previous = registered.take();
+    if let Some(previous) = previous {
+        if let Err(e) = global_shortcut.unregister(previous) {
+            log::warn!("Failed to unregister global shortcut {:?}: {}", previous, e);
+        }
+    }
+
+    let Some(shortcut) = shortcut else {
+        log::info!("Global shortcut disabled");
+        return Ok(());
+    };
+    if let Err(e) = global_shortcut.register(shortcut) {
+        log::warn!("Failed to register global shortcut {:?}: {}", shortcut, e);
+        if let Some(previous) = previous {
+            match global_shortcut.register(previous) {
+                Ok(()) => *registered = Some(previous),
+                Err(e) => log::error!("Failed to restore global shortcut {:?}: {}", previous, e),
+            }
+        }
+        return Err(format!(
+            "The shortcut {} could not be registered, it may already be in use by another application",
+            accelerator.unwrap_or_default()
+        ));
+    }
+    log::info!("Registered global shortcut {:?}", shortcut);
+    *registered = Some(shortcut);
+    Ok(())
+}
+
+/// Registers the shortcut from the settings at startup. A conflict is only
+/// logged so that the app still starts, the user can pick another shortcut.
+pub fn _setup_global_shortcut(app: &AppHandle) {
+    let settings = crate::settings::_effective_settings(app);
+    if let Err(e) = _set_global_shortcut(app, settings.global_shortcut.as_deref()) {
+        log::warn!("Global shortcut not available: {}", e);
+    }
+}
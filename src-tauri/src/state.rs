@@ -9,6 +9,53 @@ pub struct AppState {
     pub prevent_auto_update_check_for_version: String,
 }
 
+#[derive(Clone)]
+pub struct DaemonConnection {
+    pub host: String,
+    pub port: String,
+    pub token: String,
+    /// Set when connecting to a daemon started outside the app, which must
+    /// not be spawned, stopped or updated by us.
+    pub external: bool,
+    /// Unix socket path or named pipe the daemon listens on instead of
+    /// `host:port`.
+    pub socket: Option<String>,
+}
+
+impl DaemonConnection {
+    /// Host to connect to, the daemon binds to `host` which may be the
+    /// unspecified address when remote access is enabled.
+    pub fn client_host(&self) -> &str {
+        match self.host.parse::<std::net::IpAddr>() {
+            Ok(ip) if ip.is_unspecified() => crate::remote_access::LOOPBACK_HOST,
+            _ => &self.host,
+        }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}:{}", self.client_host(), self.port)
+    }
+
+    /// The webview always talks to the daemon through the proxy scheme,
+    /// which adds the token itself.
+    pub fn credentials(&self) -> DaemonCredentials {
+        DaemonCredentials {
+            url: crate::daemon_proxy::PROXY_BASE_URL.to_string(),
+        }
+    }
+}
+
+/// Sent to the main window on request and whenever the token is rotated.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonCredentials {
+    pub url: String,
+}
+
+/// Profile whose config the daemon is started with, `None` for the default.
+#[derive(Default)]
+pub struct ActiveProfile(pub Mutex<Option<String>>);
+
 pub struct PendingUpdate {
     pub pending_update: Mutex<Option<Update>>,
     pub pending_update_window_state: Mutex<Option<UpdateWindowState>>,
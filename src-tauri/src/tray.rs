@@ -0,0 +1,525 @@
+//! System tray menu, status icon and tooltip
+
+use crate::activity::RecentActivityState;
+use crate::error::SafeLock;
+use crate::pause::{PauseOption, SyncPause};
+use crate::state::DaemonConnection;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{
+    image::Image,
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    AppHandle, Manager, Wry,
+};
+use tauri_plugin_autostart::ManagerExt;
+
+pub const TRAY_ID: &str = "main";
+pub const PAUSE_SYNC_MENU_ID: &str = "pause_sync";
+pub const RESUME_SYNC_MENU_ID: &str = "resume_sync";
+
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const STARTUP_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum DaemonStatus {
+    Starting,
+    Syncing,
+    Idle,
+    Error(String),
+    Offline,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TrayStatus {
+    Starting,
+    Syncing,
+    Idle,
+    Error(String),
+    Offline,
+    Paused,
+    UpdateAvailable(String),
+    RemoteAccess,
+}
+
+pub struct TrayStatusState {
+    pub daemon_status: Mutex<DaemonStatus>,
+    pub starting_since: Mutex<Instant>,
+    pub is_sync_paused: Mutex<bool>,
+    pub update_available: Mutex<Option<String>>,
+    pub displayed_status: Mutex<Option<TrayStatus>>,
+}
+
+impl Default for TrayStatusState {
+    fn default() -> Self {
+        Self {
+            daemon_status: Mutex::new(DaemonStatus::Starting),
+            starting_since: Mutex::new(Instant::now()),
+            is_sync_paused: Mutex::new(false),
+            update_available: Mutex::new(None),
+            displayed_status: Mutex::new(None),
+        }
+    }
+}
+
+impl TrayStatus {
+    fn tooltip(&self) -> String {
+        match self {
+            TrayStatus::Starting => "SyftBox - Starting...".to_string(),
+            TrayStatus::Syncing => "SyftBox - Syncing".to_string(),
+            TrayStatus::Idle => "SyftBox - Up to date".to_string(),
+            TrayStatus::Error(error) => format!("SyftBox - Error: {}", error),
+            TrayStatus::Offline => "SyftBox - Daemon offline".to_string(),
+            TrayStatus::Paused => "SyftBox - Syncing paused".to_string(),
+            TrayStatus::UpdateAvailable(version) => {
+                format!("SyftBox - Update available ({})", version)
+            }
+            TrayStatus::RemoteAccess => {
+                "SyftBox - Remote access enabled, the daemon is reachable from the network"
+                    .to_string()
+            }
+        }
+    }
+
+    fn badge_color(&self) -> Option<[u8; 4]> {
+        match self {
+            TrayStatus::Idle => None,
+            TrayStatus::Starting => Some([158, 158, 158, 255]),
+            TrayStatus::Syncing => Some([33, 150, 243, 255]),
+            TrayStatus::Error(_) => Some([229, 57, 53, 255]),
+            TrayStatus::Offline => Some([255, 152, 0, 255]),
+            TrayStatus::Paused => Some([120, 144, 156, 255]),
+            TrayStatus::UpdateAvailable(_) => Some([67, 160, 71, 255]),
+            TrayStatus::RemoteAccess => Some([253, 216, 53, 255]),
+        }
+    }
+
+    /// Whether the status needs the user's attention. The macOS menu bar only
+    /// shows the shape of template icons, so there these get a filled badge and
+    /// the others a ring instead of a color.
+    #[cfg(target_os = "macos")]
+    fn needs_attention(&self) -> bool {
+        matches!(
+            self,
+            TrayStatus::Error(_)
+                | TrayStatus::Offline
+                | TrayStatus::UpdateAvailable(_)
+                | TrayStatus::RemoteAccess
+        )
+    }
+}
+
+pub fn _base_tray_icon(app: &AppHandle) -> Result<Image<'static>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = app;
+        Image::from_bytes(include_bytes!("../icons/tray.png"))
+            .map_err(|e| format!("Failed to load tray icon: {}", e))
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        app.default_window_icon()
+            .map(|icon| icon.clone().to_owned())
+            .ok_or_else(|| "The app has no icon for the tray".to_string())
+    }
+}
+
+/// Draws a status badge in the bottom right corner of the icon, surrounded by
+/// a transparent gap so that it stays legible on any tray background. A
+/// hollow badge is drawn as a ring.
+fn _badged_icon(base: &Image<'_>, color: [u8; 4], hollow: bool) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let radius = width.min(height) as f32 * 0.22;
+    let gap = width.min(height) as f32 * 0.06;
+    let (center_x, center_y) = (width as f32 - radius - 1.0, height as f32 - radius - 1.0);
+
+    for y in 0..height {
+        for x in 0..width {
+            let distance =
+                ((x as f32 + 0.5 - center_x).powi(2) + (y as f32 + 0.5 - center_y).powi(2)).sqrt();
+            let i = ((y * width + x) * 4) as usize;
+            if hollow && distance <= radius * 0.5 {
+                rgba[i + 3] = 0;
+            } else if distance <= radius {
+                rgba[i..i + 4].copy_from_slice(&color);
+            } else if distance <= radius + gap {
+                rgba[i + 3] = 0;
+            }
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
+
+fn _apply_tray_status(app: &AppHandle, status: &TrayStatus) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        log::debug!("Tray not ready yet, skipping status update");
+        return;
+    };
+    log::info!("Tray status changed to {:?}", status);
+
+    #[cfg(target_os = "macos")]
+    let hollow = !status.needs_attention();
+    #[cfg(not(target_os = "macos"))]
+    let hollow = false;
+    let base = match _base_tray_icon(app) {
+        Ok(base) => base,
+        Err(e) => {
+            log::error!("{}", e);
+            return;
+        }
+    };
+    let icon = match status.badge_color() {
+        Some(color) => _badged_icon(&base, color, hollow),
+        None => base,
+    };
+    if let Err(e) = tray.set_icon(Some(icon)) {
+        log::error!("Failed to update tray icon: {}", e);
+    }
+    // Setting the icon resets the template mode, which keeps the icon legible
+    // on both light and dark menu bars
+    #[cfg(target_os = "macos")]
+    if let Err(e) = tray.set_icon_as_template(true) {
+        log::error!("Failed to update tray icon template mode: {}", e);
+    }
+    if let Err(e) = tray.set_tooltip(Some(status.tooltip())) {
+        log::error!("Failed to update tray tooltip: {}", e);
+    }
+}
+
+fn _refresh_tray_status(app: &AppHandle) {
+    let state = app.state::<TrayStatusState>();
+    let daemon_status = state.daemon_status.safe_lock().clone();
+    let update_available = state.update_available.safe_lock().clone();
+    let is_sync_paused = *state.is_sync_paused.safe_lock();
+    let is_remote_access = crate::remote_access::_is_remote_access_active(app);
+
+    let status = match (daemon_status, update_available) {
+        _ if is_sync_paused => TrayStatus::Paused,
+        (DaemonStatus::Error(error), _) => TrayStatus::Error(error),
+        (DaemonStatus::Offline, _) => TrayStatus::Offline,
+        (DaemonStatus::Starting, _) => TrayStatus::Starting,
+        // Stays visible for as long as the daemon is exposed
+        _ if is_remote_access => TrayStatus::RemoteAccess,
+        (DaemonStatus::Syncing, _) => TrayStatus::Syncing,
+        (DaemonStatus::Idle, Some(version)) => TrayStatus::UpdateAvailable(version),
+        (DaemonStatus::Idle, None) => TrayStatus::Idle,
+    };
+
+    // Applied after releasing the lock, as the tray setters wait for the main
+    // thread which may be refreshing the status itself
+    let is_changed = {
+        let mut displayed_status = state.displayed_status.safe_lock();
+        let is_changed = displayed_status.as_ref() != Some(&status);
+        *displayed_status = Some(status.clone());
+        is_changed
+    };
+    if is_changed {
+        _apply_tray_status(app, &status);
+    }
+}
+
+pub fn _set_daemon_status(app: &AppHandle, daemon_status: DaemonStatus) {
+    let state = app.state::<TrayStatusState>();
+    let previous_status =
+        std::mem::replace(&mut *state.daemon_status.safe_lock(), daemon_status.clone());
+    if daemon_status == DaemonStatus::Starting && previous_status != DaemonStatus::Starting {
+        *state.starting_since.safe_lock() = Instant::now();
+    }
+    _refresh_tray_status(app);
+}
+
+pub fn _set_update_available(app: &AppHandle, version: Option<String>) {
+    *app.state::<TrayStatusState>().update_available.safe_lock() = version;
+    _refresh_tray_status(app);
+}
+
+/// Handles to the tray menu items whose state changes at runtime.
+#[derive(Clone)]
+pub struct TrayMenuItems {
+    pub menu: Menu<Wry>,
+    autostart: CheckMenuItem<Wry>,
+    check_for_updates: MenuItem<Wry>,
+    pause_sync: Submenu<Wry>,
+    pause_options: Vec<(PauseOption, CheckMenuItem<Wry>)>,
+    resume_sync: MenuItem<Wry>,
+}
+
+/// Owns the tray menu and keeps its check states, labels and enabled flags in
+/// sync with the app state, wherever that state was changed from.
+#[derive(Default)]
+pub struct TrayController {
+    items: Mutex<Option<TrayMenuItems>>,
+    pub is_checking_for_updates: AtomicBool,
+}
+
+/// Builds the tray menu. Called again whenever the menu structure changes,
+/// e.g. when new activity arrives. Item states are applied by
+/// [`_refresh_tray_menu`].
+pub fn _build_tray_menu(app: &AppHandle) -> TrayMenuItems {
+    let show_dashboard_i =
+        MenuItem::with_id(app, "show_dashboard", "Open SyftBox", true, None::<&str>)
+            .expect("Failed to create Show Dashboard menu item");
+    let open_workspace_i = MenuItem::with_id(
+        app,
+        "open_workspace",
+        "Open Workspace Folder",
+        true,
+        None::<&str>,
+    )
+    .expect("Failed to create Open Workspace Folder menu item");
+    let open_logs_i = MenuItem::with_id(app, "open_logs", "Open Logs Folder", true, None::<&str>)
+        .expect("Failed to create Open Logs Folder menu item");
+    let copy_diagnostics_i = MenuItem::with_id(
+        app,
+        "copy_diagnostics",
+        "Copy Diagnostics",
+        true,
+        None::<&str>,
+    )
+    .expect("Failed to create Copy Diagnostics menu item");
+    let save_diagnostics_bundle_i = MenuItem::with_id(
+        app,
+        "save_diagnostics_bundle",
+        "Save Diagnostics Bundle",
+        true,
+        None::<&str>,
+    )
+    .expect("Failed to create Save Diagnostics Bundle menu item");
+    let pause_options: Vec<(PauseOption, CheckMenuItem<Wry>)> = PauseOption::ALL
+        .into_iter()
+        .map(|option| {
+            let item = CheckMenuItem::with_id(
+                app,
+                option.menu_id(),
+                option.label(),
+                true,
+                false,
+                None::<&str>,
+            )
+            .expect("Failed to create Pause Syncing menu item");
+            (option, item)
+        })
+        .collect();
+    let resume_sync_i = MenuItem::with_id(
+        app,
+        RESUME_SYNC_MENU_ID,
+        "Resume Syncing",
+        false,
+        None::<&str>,
+    )
+    .expect("Failed to create Resume Syncing menu item");
+    let separator_i =
+        PredefinedMenuItem::separator(app).expect("Failed to create separator menu item");
+    let mut pause_sync_items: Vec<&dyn IsMenuItem<Wry>> = pause_options
+        .iter()
+        .map(|(_, item)| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    pause_sync_items.push(&separator_i);
+    pause_sync_items.push(&resume_sync_i);
+    let pause_sync_i = Submenu::with_id_and_items(
+        app,
+        PAUSE_SYNC_MENU_ID,
+        "Pause Syncing",
+        true,
+        &pause_sync_items,
+    )
+    .expect("Failed to create Pause Syncing menu");
+    let recent_activity_i = _build_recent_activity_menu(app);
+    let autostart_i =
+        CheckMenuItem::with_id(app, "autostart", "Autostart", true, false, None::<&str>)
+            .expect("Failed to create Autostart menu item");
+    let check_for_updates_i = MenuItem::with_id(
+        app,
+        "check_for_updates",
+        "Check for Updates",
+        true,
+        None::<&str>,
+    )
+    .expect("Failed to create Check for Updates menu item");
+    let about_i = MenuItem::with_id(app, "about", "About SyftBox", true, None::<&str>)
+        .expect("Failed to create About menu item");
+    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)
+        .expect("Failed to create Quit menu item");
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_dashboard_i,
+            &open_workspace_i,
+            &open_logs_i,
+            &copy_diagnostics_i,
+            &save_diagnostics_bundle_i,
+            &recent_activity_i,
+            &pause_sync_i,
+            &autostart_i,
+            &check_for_updates_i,
+            &about_i,
+            &quit_i,
+        ],
+    )
+    .expect("Failed to create menu");
+
+    TrayMenuItems {
+        menu,
+        autostart: autostart_i,
+        check_for_updates: check_for_updates_i,
+        pause_sync: pause_sync_i,
+        pause_options,
+        resume_sync: resume_sync_i,
+    }
+}
+
+fn _build_recent_activity_menu(app: &AppHandle) -> Submenu<Wry> {
+    let activity = app.state::<RecentActivityState>();
+    let activity = activity.events.safe_lock();
+    let activity_items: Vec<MenuItem<Wry>> = activity
+        .iter()
+        .map(|event| {
+            MenuItem::with_id(app, event.menu_id(), event.label(), true, None::<&str>)
+                .expect("Failed to create Recent Activity menu item")
+        })
+        .collect();
+    let empty_i = MenuItem::with_id(
+        app,
+        "no_activity",
+        "No Recent Activity",
+        false,
+        None::<&str>,
+    )
+    .expect("Failed to create Recent Activity menu item");
+    let items: Vec<&dyn IsMenuItem<Wry>> = if activity_items.is_empty() {
+        vec![&empty_i]
+    } else {
+        activity_items
+            .iter()
+            .map(|item| item as &dyn IsMenuItem<Wry>)
+            .collect()
+    };
+    Submenu::with_id_and_items(app, "recent_activity", "Recent Activity", true, &items)
+        .expect("Failed to create Recent Activity menu")
+}
+
+/// Installs a freshly built menu on the tray and takes ownership of its items.
+pub fn _set_tray_menu(app: &AppHandle, items: TrayMenuItems) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Err(e) = tray.set_menu(Some(items.menu.clone())) {
+            log::error!("Failed to set tray menu: {}", e);
+            return;
+        }
+    }
+    *app.state::<TrayController>().items.safe_lock() = Some(items);
+    _refresh_tray_menu(app);
+}
+
+pub fn _rebuild_tray_menu(app: &AppHandle) {
+    log::debug!("Rebuilding tray menu");
+    _set_tray_menu(app, _build_tray_menu(app));
+}
+
+/// Applies the current app state to the tray menu items.
+///
+/// The menu setters wait for the main thread, which also refreshes the menu
+/// from tray events, so they are called on cloned handles without holding the
+/// controller's lock.
+pub fn _refresh_tray_menu(app: &AppHandle) {
+    let controller = app.state::<TrayController>();
+    let Some(items) = controller.items.safe_lock().clone() else {
+        return;
+    };
+    let policy = app.state::<crate::policy::Policy>();
+    let sync_pause = crate::pause::_get_sync_pause(app);
+    let is_checking_for_updates = controller.is_checking_for_updates.load(Ordering::SeqCst);
+
+    let results = [
+        items
+            .autostart
+            .set_checked(app.autolaunch().is_enabled().unwrap_or(false)),
+        items.autostart.set_enabled(policy.autostart.is_none()),
+        items
+            .check_for_updates
+            .set_enabled(!policy.disable_auto_update && !is_checking_for_updates),
+        items
+            .check_for_updates
+            .set_text(if is_checking_for_updates {
+                "Checking for Updates..."
+            } else {
+                "Check for Updates"
+            }),
+        items.pause_sync.set_text(if sync_pause.is_some() {
+            "Syncing Paused"
+        } else {
+            "Pause Syncing"
+        }),
+        items.resume_sync.set_enabled(sync_pause.is_some()),
+    ];
+    let pause_results = items.pause_options.iter().map(|(option, item)| {
+        item.set_checked(sync_pause.is_some_and(|pause| pause.option == *option))
+    });
+    for result in results.into_iter().chain(pause_results) {
+        if let Err(e) = result {
+            log::error!("Failed to update tray menu: {}", e);
+        }
+    }
+}
+
+pub fn _set_checking_for_updates(app: &AppHandle, is_checking: bool) {
+    app.state::<TrayController>()
+        .is_checking_for_updates
+        .store(is_checking, Ordering::SeqCst);
+    _refresh_tray_menu(app);
+}
+
+pub fn _set_sync_pause(app: &AppHandle, pause: Option<SyncPause>) {
+    *app.state::<TrayStatusState>().is_sync_paused.safe_lock() = pause.is_some();
+    _refresh_tray_status(app);
+    _refresh_tray_menu(app);
+}
+
+async fn _poll_daemon_status(connection: &DaemonConnection) -> Result<DaemonStatus, String> {
+    let status = crate::daemon_client::_get_daemon_status(connection).await?;
+
+    let datasite = &status["datasite"];
+    Ok(match datasite["status"].as_str() {
+        Some("ERROR") => DaemonStatus::Error(
+            datasite["error"]
+                .as_str()
+                .unwrap_or("Unknown error")
+                .to_string(),
+        ),
+        Some("PROVISIONING") => DaemonStatus::Starting,
+        _ => DaemonStatus::Idle,
+    })
+}
+
+pub fn _start_status_polling(app: &AppHandle) {
+    log::info!("Starting daemon status polling for the tray");
+    _refresh_tray_status(app);
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if crate::pause::_is_sync_paused(&app_handle) {
+                tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+                continue;
+            }
+            let connection = crate::daemon_client::_get_daemon_connection(&app_handle);
+            let daemon_status = match _poll_daemon_status(&connection).await {
+                Ok(daemon_status) => daemon_status,
+                Err(e) => {
+                    log::debug!("Daemon status poll failed: {}", e);
+                    let state = app_handle.state::<TrayStatusState>();
+                    let is_starting = *state.daemon_status.safe_lock() == DaemonStatus::Starting;
+                    let starting_since = *state.starting_since.safe_lock();
+                    if is_starting && starting_since.elapsed() < STARTUP_GRACE_PERIOD {
+                        DaemonStatus::Starting
+                    } else {
+                        DaemonStatus::Offline
+                    }
+                }
+            };
+            _set_daemon_status(&app_handle, daemon_status);
+            tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+        }
+    });
+}
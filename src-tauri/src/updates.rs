@@ -1,27 +1,69 @@
 //! Update-related functionality
 
+use crate::error::SafeLock;
+use crate::notifications::{NotificationCategory, NotificationTarget};
 use crate::state::{AppState, PendingUpdate, UpdateWindowType};
 use crate::windows::_show_update_window;
-use std::{sync::Mutex, thread, time::Duration};
+use std::{sync::Mutex, time::Duration};
 use tauri::{AppHandle, Manager};
-use tauri_plugin_updater::UpdaterExt;
+use tauri_plugin_updater::{Updater, UpdaterExt};
 
 pub fn _start_periodic_update_checks(app: &AppHandle) {
+    if app.state::<crate::cli::CliArgs>().no_update_check {
+        log::info!("Update checks are disabled from the command line");
+        return;
+    }
     log::info!("Starting periodic update checks");
     let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
         loop {
-            _check_for_updates(&app_handle, false).await;
-            thread::sleep(Duration::from_secs(3600)); // Sleep for 1 hour
+            if crate::settings::_effective_settings(&app_handle).auto_update_check {
+                _check_for_updates(&app_handle, false).await;
+                #[cfg(not(debug_assertions))]
+                crate::daemon_updates::_check_for_daemon_updates(&app_handle).await;
+            } else {
+                log::debug!("Automatic update checks are disabled");
+            }
+            tokio::time::sleep(Duration::from_secs(3600)).await; // Sleep for 1 hour
         }
     });
 }
 
+/// Marks an update check as running in the tray for as long as it is alive.
+struct UpdateCheckGuard<'a>(&'a AppHandle);
+
+impl<'a> UpdateCheckGuard<'a> {
+    fn new(app: &'a AppHandle) -> Self {
+        crate::tray::_set_checking_for_updates(app, true);
+        Self(app)
+    }
+}
+
+impl Drop for UpdateCheckGuard<'_> {
+    fn drop(&mut self) {
+        crate::tray::_set_checking_for_updates(self.0, false);
+    }
+}
+
+fn _build_updater(app: &AppHandle) -> Result<Updater, String> {
+    let channel = crate::settings::_effective_settings(app).update_channel;
+    let endpoint = crate::policy::_update_endpoint_for_channel(&channel)?;
+    log::debug!("Using update channel {} ({})", channel, endpoint);
+    let url = endpoint
+        .parse()
+        .map_err(|e| format!("Invalid update endpoint {}: {}", endpoint, e))?;
+    app.updater_builder()
+        .endpoints(vec![url])
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Failed to create updater: {}", e))
+}
+
 pub async fn _check_for_updates(app: &AppHandle, has_user_checked_manually: bool) {
     log::info!(
         "Checking for updates (manual check: {})",
         has_user_checked_manually
     );
+    let _guard = UpdateCheckGuard::new(app);
     let handle = app.clone();
     let current_version = handle.package_info().version.to_string();
 
@@ -36,7 +78,10 @@ pub async fn _check_for_updates(app: &AppHandle, has_user_checked_manually: bool
             0,
         );
     }
-    let update_result = handle.updater().unwrap().check().await;
+    let update_result = match _build_updater(app) {
+        Ok(updater) => updater.check().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
     match update_result {
         Ok(Some(update)) => {
             log::info!(
@@ -44,9 +89,10 @@ pub async fn _check_for_updates(app: &AppHandle, has_user_checked_manually: bool
                 update.version,
                 update.current_version
             );
+            crate::tray::_set_update_available(app, Some(update.version.clone()));
             let app_state_mutex = app.state::<Mutex<AppState>>();
             let should_check_for_update = {
-                let state_guard = app_state_mutex.lock().unwrap();
+                let state_guard = app_state_mutex.safe_lock();
                 has_user_checked_manually
                     || state_guard.prevent_auto_update_check_for_version != update.version
             };
@@ -58,6 +104,15 @@ pub async fn _check_for_updates(app: &AppHandle, has_user_checked_manually: bool
                 return;
             }
 
+            if !has_user_checked_manually {
+                crate::notifications::_notify(
+                    app,
+                    NotificationCategory::UpdateAvailable,
+                    "SyftBox update available",
+                    &format!("SyftBox {} is ready to install.", update.version),
+                    NotificationTarget::Window("updates"),
+                );
+            }
             _show_update_window(
                 app,
                 UpdateWindowType::Available,
@@ -74,10 +129,11 @@ pub async fn _check_for_updates(app: &AppHandle, has_user_checked_manually: bool
             let pending_update_state = app.state::<PendingUpdate>();
             // The actual `Update` struct comes from `tauri_plugin_updater::Update`
             // This assignment should be fine as `pending_update_state.pending_update` is `Mutex<Option<tauri_plugin_updater::Update>>`
-            *pending_update_state.pending_update.lock().unwrap() = Some(update);
+            *pending_update_state.pending_update.safe_lock() = Some(update);
         }
         Ok(None) => {
             log::info!("No updates available");
+            crate::tray::_set_update_available(app, None);
             if has_user_checked_manually {
                 _show_update_window(
                     app,
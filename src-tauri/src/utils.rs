@@ -1,27 +1,58 @@
 //! Utility functions and helpers
 
-use tauri::{
-    menu::{CheckMenuItem, Menu, MenuItem},
-    tray::TrayIconBuilder,
-};
+use crate::pause::PauseOption;
+use std::path::PathBuf;
+use tauri::tray::{TrayIconBuilder, TrayIconEvent};
 use tauri::{AppHandle, Manager, WebviewUrl};
 use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_opener::OpenerExt;
 
 #[cfg(not(debug_assertions))]
 use {
-    std::{thread, time::Duration},
+    crate::error::SafeLock,
+    std::{sync::Mutex, thread, time::Duration},
     sysinfo::System,
     tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind},
-    tauri_plugin_shell::ShellExt,
+    tauri_plugin_shell::{
+        process::{CommandChild, CommandEvent},
+        ShellExt,
+    },
 };
 
-#[cfg(target_os = "macos")]
-use tauri::image::Image;
+/// The running process-wick sidecar, which kills the child processes it was
+/// started with when the app dies.
+#[cfg(not(debug_assertions))]
+#[derive(Default)]
+pub struct ProcessWick(Mutex<Option<CommandChild>>);
+
+/// Falls back to the temp dir without a home directory, as the panic hook
+/// writes its crash files here.
+/// Only fails when the OS has no data directory for the current user.
+pub fn _app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app directory: {}", e))
+}
 
-pub fn _generate_daemon_client_args() -> (String, String, String) {
+pub fn _logs_dir() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".syftbox"))
+        .unwrap_or_else(|| std::env::temp_dir().join("syftbox"))
+        .join("logs")
+}
+
+pub fn _generate_daemon_client_args(
+    port_override: Option<u16>,
+    allow_remote_access: bool,
+) -> (String, String, String) {
     #[cfg(debug_assertions)]
     {
         log::debug!("Generating daemon client args for debug mode");
+        if let Some(port) = port_override {
+            log::warn!("Ignoring policy daemon port {} in debug mode", port);
+        }
+        let _ = allow_remote_access;
         let daemon_host =
             std::env::var("DAEMON_HOST").expect("DAEMON_HOST environment variable is not set");
         let daemon_port =
@@ -33,25 +64,39 @@ pub fn _generate_daemon_client_args() -> (String, String, String) {
     #[cfg(not(debug_assertions))]
     {
         log::debug!("Generating daemon client args for release mode");
-        let daemon_host = std::env::var("DAEMON_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-        let daemon_port = "7938".to_string(); // Fixed port for release
+        let daemon_host = crate::remote_access::_validate_bind_host(
+            std::env::var("DAEMON_HOST")
+                .unwrap_or_else(|_| crate::remote_access::LOOPBACK_HOST.to_string()),
+            allow_remote_access,
+        );
+        let daemon_port = port_override.unwrap_or(7938).to_string(); // Fixed port for release
         let daemon_token = _generate_secure_token();
         (daemon_host, daemon_port, daemon_token)
     }
 }
 
-pub fn _generate_main_url(host: &str, port: &str, token: &str) -> WebviewUrl {
-    log::debug!("Generating main URL with host: {}, port: {}", host, port);
-    let url = format!("#host={}&port={}&token={}", host, port, token);
-    WebviewUrl::App(url.into())
+/// Passed by autostart so that login launches only start the tray and daemon.
+pub const HIDDEN_ARG: &str = "--hidden";
+
+pub fn _is_started_hidden(app: &AppHandle) -> bool {
+    app.state::<crate::cli::CliArgs>().hidden
+}
+
+/// The main window asks for the daemon credentials with the
+/// `get_daemon_credentials` command, so they never appear in its URL.
+pub fn _generate_main_url() -> WebviewUrl {
+    WebviewUrl::App(Default::default())
 }
 
 #[cfg(not(debug_assertions))]
 pub fn _is_app_updated(app: &AppHandle) -> bool {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .expect("Failed to get app directory");
+    let app_data_dir = match _app_data_dir(app) {
+        Ok(app_data_dir) => app_data_dir,
+        Err(e) => {
+            log::error!("Failed to check whether the app was updated: {}", e);
+            return false;
+        }
+    };
     std::fs::create_dir_all(&app_data_dir).unwrap_or_else(|e| {
         log::error!(
             "Failed to create app data dir {}: {}",
@@ -77,17 +122,32 @@ pub fn _is_app_updated(app: &AppHandle) -> bool {
     result
 }
 
+/// Verifies and starts the sidecars off the main thread, so that neither the
+/// hashing nor waiting for the port delays the app's startup.
 #[cfg(not(debug_assertions))]
 pub fn _setup_sidecars_for_release_builds(
     app: &AppHandle,
-    daemon_host: &str,
     daemon_port: &str,
-    daemon_token: &str,
     is_app_updated: bool,
 ) {
     log::info!("Setting up sidecars");
+    let is_sync_paused = crate::pause::_restore_sync_pause(app);
+    let app_handle = app.clone();
+    let daemon_port = daemon_port.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        if crate::integrity::_ensure_sidecars_intact(&app_handle) {
+            _start_sidecars(&app_handle, &daemon_port, is_app_updated, is_sync_paused);
+        }
+    });
+}
+
+#[cfg(not(debug_assertions))]
+fn _start_sidecars(app: &AppHandle, daemon_port: &str, is_app_updated: bool, is_sync_paused: bool) {
+    let uses_socket = crate::daemon_client::_get_daemon_connection(app)
+        .socket
+        .is_some();
 
-    if _is_port_in_use(daemon_port) {
+    if !uses_socket && _is_port_in_use(daemon_port) {
         if is_app_updated {
             log::info!(
                 "App was just updated and port is still in use, silently waiting for clean up"
@@ -105,7 +165,7 @@ pub fn _setup_sidecars_for_release_builds(
         }
     }
 
-    while _is_port_in_use(daemon_port) {
+    while !uses_socket && _is_port_in_use(daemon_port) {
         if is_app_updated {
             log::info!("Port is still in use after waiting, showing dialog");
         } else {
@@ -132,103 +192,95 @@ pub fn _setup_sidecars_for_release_builds(
         }
     }
 
-    let app_handle_clone = app.app_handle().clone();
-    let daemon_host = daemon_host.to_string();
-    let daemon_port = daemon_port.to_string();
-    let daemon_token = daemon_token.to_string();
-    tauri::async_runtime::spawn(async move {
-        let daemon_sidecar_exit_code = app_handle_clone
-            .shell()
-            .sidecar("syftboxd")
-            .unwrap()
-            .args([
-                "daemon",
-                "--http-addr",
-                &format!("{}:{}", daemon_host, daemon_port),
-                "--http-token",
-                &daemon_token,
-            ])
-            .env(
-                "SYFTBOX_DESKTOP_BINARIES_PATH",
-                std::env::current_exe()
-                    .unwrap()
-                    .parent()
-                    .unwrap()
-                    .to_str()
-                    .unwrap(),
-            )
-            .status()
-            .await
-            .unwrap()
-            .code()
-            .unwrap_or_else(|| {
-                log::warn!("syftboxd sidecar exited without a status code");
-                1
-            });
-
-        log::error!(
-            "syftboxd sidecar exited unexpectedly with code: {}",
-            daemon_sidecar_exit_code
-        );
-        app_handle_clone
-            .dialog()
+    if is_sync_paused {
+        log::info!("Sync is paused, not starting the daemon");
+    } else if let Err(e) = crate::daemon::_spawn_daemon(app) {
+        log::error!("{}", e);
+        app.dialog()
             .message(
-                "SyftBox daemon exited unexpectedly. Please check the logs for more information.",
+                "Failed to start the SyftBox daemon. Please check the logs for more information.",
             )
             .kind(MessageDialogKind::Error)
             .title("Error")
             .blocking_show();
+        app.exit(1);
+        return;
+    }
 
-        app_handle_clone.exit(daemon_sidecar_exit_code);
-    });
+    _start_process_wick(app);
+}
 
+/// Keeps process-wick running, with the child processes at the time of each
+/// start as its targets.
+#[cfg(not(debug_assertions))]
+fn _start_process_wick(app: &AppHandle) {
     let main_process_pid = std::process::id();
-    let child_process_pids = _find_child_process_pids();
-
-    // Spawn process-wick sidecar with restart mechanism
-    let app_handle_clone = app.app_handle().clone();
+    let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
         loop {
-            log::info!("Starting process-wick sidecar");
-            let process_wick_sidecar_exit_code = app_handle_clone
+            if !crate::integrity::_is_sidecar_intact(&app_handle, "process-wick") {
+                log::error!("Refusing to spawn process-wick: integrity check failed");
+                break;
+            }
+            let child_process_pids = _find_child_process_pids();
+            log::info!(
+                "Starting process-wick sidecar watching {:?}",
+                child_process_pids
+            );
+            let spawn_result = app_handle
                 .shell()
                 .sidecar("process-wick")
-                .unwrap()
-                .args([
-                    "--dog",
-                    &main_process_pid.to_string(),
-                    "--targets",
-                    &child_process_pids.join(","),
-                    "--log-file",
-                    dirs::home_dir()
-                        .expect("Failed to get home directory")
-                        .join(".syftbox")
-                        .join("logs")
-                        .join("process-wick.log")
-                        .to_str()
-                        .unwrap(),
-                ])
-                .status()
-                .await
-                .unwrap()
-                .code()
-                .unwrap_or_else(|| {
-                    log::warn!("process-wick sidecar exited without a status code");
-                    1
+                .and_then(|command| {
+                    command
+                        .args([
+                            "--dog",
+                            &main_process_pid.to_string(),
+                            "--targets",
+                            &child_process_pids.join(","),
+                            "--log-file",
+                            &_logs_dir().join("process-wick.log").to_string_lossy(),
+                        ])
+                        .spawn()
                 });
-
-            // Wait for the sidecar to exit
-            log::warn!(
-                "process-wick sidecar exited with status: {:?}, restarting...",
-                process_wick_sidecar_exit_code
-            );
+            match spawn_result {
+                Ok((mut rx, child)) => {
+                    *app_handle.state::<ProcessWick>().0.safe_lock() = Some(child);
+                    let mut exit_code = None;
+                    while let Some(event) = rx.recv().await {
+                        if let CommandEvent::Terminated(payload) = event {
+                            exit_code = payload.code;
+                            break;
+                        }
+                    }
+                    log::warn!(
+                        "process-wick sidecar exited with status: {:?}, restarting...",
+                        exit_code
+                    );
+                }
+                Err(e) => log::error!("Failed to spawn process-wick sidecar: {}", e),
+            }
 
             // Small delay before restarting to avoid rapid restart loops
-            thread::sleep(Duration::from_secs(1));
+            tokio::time::sleep(Duration::from_secs(1)).await;
         }
     });
 }
 
+/// Restarts process-wick so that it also watches a newly spawned daemon,
+/// which it would otherwise leave behind if the app dies.
+#[cfg(not(debug_assertions))]
+pub fn _retarget_process_wick(app: &AppHandle) {
+    let Some(process_wick) = app.try_state::<ProcessWick>() else {
+        return;
+    };
+    if let Some(child) = process_wick.0.safe_lock().take() {
+        log::debug!("Restarting process-wick to watch the new daemon");
+        if let Err(e) = child.kill() {
+            log::error!("Failed to stop process-wick sidecar: {}", e);
+        }
+    }
+}
+
 #[cfg(not(debug_assertions))]
 pub fn _find_child_process_pids() -> Vec<String> {
     let mut sys = System::new_all();
@@ -236,6 +288,10 @@ pub fn _find_child_process_pids() -> Vec<String> {
     let current_pid = sysinfo::Pid::from_u32(std::process::id());
     let mut child_process_pids = Vec::new();
     for (pid, process) in sys.processes() {
+        // process-wick does not watch itself
+        if process.name().to_string_lossy().contains("process-wick") {
+            continue;
+        }
         if let Some(parent_pid) = process.parent() {
             if parent_pid == current_pid {
                 child_process_pids.push(pid.to_string());
@@ -247,60 +303,41 @@ pub fn _find_child_process_pids() -> Vec<String> {
 
 pub fn _setup_system_tray(app: &AppHandle) {
     log::info!("Setting up system tray");
-    let autostart_manager = app.autolaunch();
-
-    let show_dashboard_i =
-        MenuItem::with_id(app, "show_dashboard", "Open SyftBox", true, None::<&str>)
-            .expect("Failed to create Show Dashboard menu item");
-    let autostart_i = CheckMenuItem::with_id(
-        app,
-        "autostart",
-        "Autostart",
-        true,
-        autostart_manager.is_enabled().unwrap_or(false),
-        None::<&str>,
-    )
-    .expect("Failed to create Autostart menu item");
-    let check_for_updates_i = MenuItem::with_id(
-        app,
-        "check_for_updates",
-        "Check for Updates",
-        true,
-        None::<&str>,
-    )
-    .expect("Failed to create Check for Updates menu item");
-    let about_i = MenuItem::with_id(app, "about", "About SyftBox", true, None::<&str>)
-        .expect("Failed to create About menu item");
-    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)
-        .expect("Failed to create Quit menu item");
-
-    let menu = Menu::with_items(
-        app,
-        &[
-            &show_dashboard_i,
-            &autostart_i,
-            &check_for_updates_i,
-            &about_i,
-            &quit_i,
-        ],
-    )
-    .expect("Failed to create menu");
-
-    let mut tray_builder = TrayIconBuilder::new().menu(&menu);
+    let menu_items = crate::tray::_build_tray_menu(app);
 
-    #[cfg(target_os = "macos")]
-    {
-        tray_builder = tray_builder
-            .icon(Image::from_bytes(include_bytes!("../icons/tray.png")).unwrap())
-            .icon_as_template(true);
+    let mut tray_builder = TrayIconBuilder::with_id(crate::tray::TRAY_ID)
+        .menu(&menu_items.menu)
+        .tooltip("SyftBox");
+    match crate::tray::_base_tray_icon(app) {
+        Ok(icon) => tray_builder = tray_builder.icon(icon),
+        Err(e) => log::error!("{}", e),
     }
-    #[cfg(not(target_os = "macos"))]
+
+    #[cfg(target_os = "macos")]
     {
-        tray_builder = tray_builder.icon(app.default_window_icon().unwrap().clone());
+        tray_builder = tray_builder.icon_as_template(true);
     }
 
-    let tray = tray_builder.build(app).unwrap();
+    let tray = match tray_builder.build(app) {
+        Ok(tray) => tray,
+        Err(e) => {
+            log::error!("Failed to create system tray: {}", e);
+            return;
+        }
+    };
     log::debug!("System tray created successfully");
+    crate::tray::_set_tray_menu(app, menu_items);
+
+    // Refresh on any tray interaction so that state changed outside the app,
+    // e.g. autostart toggled in the OS settings, is shown before the menu opens
+    tray.on_tray_icon_event(|tray, event| {
+        if matches!(
+            event,
+            TrayIconEvent::Click { .. } | TrayIconEvent::Enter { .. }
+        ) {
+            crate::tray::_refresh_tray_menu(tray.app_handle());
+        }
+    });
 
     let app_handle_clone = app.clone(); // Clone AppHandle for the event handler
     tray.on_menu_event(move |event_app, event| match event.id.as_ref() {
@@ -310,19 +347,71 @@ pub fn _setup_system_tray(app: &AppHandle) {
         }
         "autostart" => {
             log::info!("Autostart menu item clicked");
-            let manager = event_app.autolaunch();
-            if manager.is_enabled().unwrap_or(false) {
-                if let Err(e) = manager.disable() {
-                    log::error!("Failed to disable autostart: {}", e);
-                } else {
-                    log::debug!("Autostart disabled");
+            let is_enabled = event_app.autolaunch().is_enabled().unwrap_or(false);
+            if let Err(e) = crate::settings::_set_autostart(event_app, !is_enabled) {
+                log::error!("{}", e);
+            } else {
+                log::debug!(
+                    "Autostart {}",
+                    if is_enabled { "disabled" } else { "enabled" }
+                );
+            }
+        }
+        "open_workspace" => {
+            log::info!("Open workspace folder menu item clicked");
+            let app_for_workspace = event_app.clone();
+            tauri::async_runtime::spawn(async move {
+                match crate::daemon_client::_get_workspace_dir(&app_for_workspace).await {
+                    Ok(workspace_dir) => _open_folder(&app_for_workspace, &workspace_dir),
+                    Err(e) => log::error!("Failed to get workspace folder: {}", e),
                 }
-            } else if let Err(e) = manager.enable() {
-                log::error!("Failed to enable autostart: {}", e);
+            });
+        }
+        "open_logs" => {
+            log::info!("Open logs folder menu item clicked");
+            _open_folder(event_app, &_logs_dir());
+        }
+        "copy_diagnostics" => {
+            log::info!("Copy diagnostics menu item clicked");
+            let summary = crate::diagnostics::_diagnostics_summary(event_app);
+            if let Err(e) = event_app.clipboard().write_text(summary) {
+                log::error!("Failed to copy diagnostics to clipboard: {}", e);
+            }
+        }
+        "save_diagnostics_bundle" => {
+            log::info!("Save diagnostics bundle menu item clicked");
+            let app_for_bundle = event_app.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                match crate::diagnostics_bundle::_save_diagnostics_bundle(&app_for_bundle) {
+                    Ok(path) => {
+                        if let Err(e) = app_for_bundle.opener().reveal_item_in_dir(&path) {
+                            log::error!("Failed to reveal {}: {}", path.display(), e);
+                        }
+                    }
+                    Err(e) => log::error!("Failed to save diagnostics bundle: {}", e),
+                }
+            });
+        }
+        crate::tray::RESUME_SYNC_MENU_ID => {
+            log::info!("Resume syncing menu item clicked");
+            crate::pause::_resume_sync(event_app);
+        }
+        id if PauseOption::from_menu_id(id).is_some() => {
+            let Some(option) = PauseOption::from_menu_id(id) else {
+                return;
+            };
+            log::info!("Pause syncing menu item clicked: {:?}", option);
+            if crate::pause::_get_sync_pause(event_app).is_some_and(|pause| pause.option == option)
+            {
+                crate::pause::_resume_sync(event_app);
             } else {
-                log::debug!("Autostart enabled");
+                crate::pause::_pause_sync(event_app, option);
             }
         }
+        id if id.starts_with(crate::activity::ACTIVITY_MENU_ID_PREFIX) => {
+            log::info!("Recent activity menu item clicked: {}", id);
+            crate::activity::_open_activity(event_app, id);
+        }
         "check_for_updates" => {
             log::info!("Check for updates menu item clicked");
             let app_for_updates = app_handle_clone.clone(); // Use the cloned AppHandle
@@ -394,13 +483,44 @@ pub fn _generate_secure_token() -> String {
     hex::encode(key)
 }
 
+fn _open_folder(app: &AppHandle, path: &std::path::Path) {
+    log::debug!("Opening folder {}", path.display());
+    if let Err(e) = app.opener().open_path(path.to_string_lossy(), None::<&str>) {
+        log::error!("Failed to open folder {}: {}", path.display(), e);
+    }
+}
+
 pub fn show_main_window(app: &AppHandle) {
+    if crate::headless::_is_headless(app) {
+        crate::headless::_open_in_browser(app);
+        return;
+    }
     log::debug!("Showing main window");
-    let window = app.get_webview_window("main").unwrap();
-    window.set_skip_taskbar(false).unwrap();
+    let Some(window) = crate::windows::_get_or_create_main_window(app) else {
+        return;
+    };
+    #[cfg(target_os = "macos")]
+    let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
+    if let Err(e) = window
+        .set_skip_taskbar(false)
+        .and_then(|_| window.show())
+        .and_then(|_| window.set_focus())
+    {
+        log::error!("Failed to show main window: {}", e);
+    }
+}
+
+pub fn hide_main_window(app: &AppHandle) {
+    log::debug!("Hiding main window");
+    let Some(window) = app.get_webview_window("main") else {
+        log::warn!("Main window not found when attempting to hide it");
+        return;
+    };
+    crate::window_geometry::_record_window_geometry(&window);
+    crate::window_geometry::_save_window_geometry(app);
+    if let Err(e) = window.hide().and_then(|_| window.set_skip_taskbar(true)) {
+        log::error!("Failed to hide main window: {}", e);
+    }
     #[cfg(target_os = "macos")]
-    app.set_activation_policy(tauri::ActivationPolicy::Regular)
-        .unwrap();
-    window.show().unwrap();
-    window.set_focus().unwrap();
+    let _ = app.set_activation_policy(tauri::ActivationPolicy::Accessory);
 }
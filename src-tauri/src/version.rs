@@ -4,3 +4,6 @@ pub const DESKTOP_BUILD: &str = "2025-07-13T23:34:09+05:30";
 pub const DAEMON_VERSION: &str = "0.6.1";
 pub const DAEMON_HASH: &str = "d2dde1c";
 pub const DAEMON_BUILD: &str = "2025-07-11T01:26:37+05:30";
+
+// SHA-256 hashes of the bundled sidecar binaries, generated by build.rs
+include!(concat!(env!("OUT_DIR"), "/sidecar_hashes.rs"));
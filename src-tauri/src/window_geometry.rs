@@ -0,0 +1,193 @@
+//! Saving and restoring the main window position and size
+
+use crate::error::SafeLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+/// Delay before writing the geometry after the last move or resize.
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Physical coordinates of the window while it is neither maximized nor
+/// fullscreen, so that un-maximizing after a restore returns to these bounds.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub monitor: Option<String>,
+}
+
+#[derive(Default)]
+pub struct WindowGeometryState {
+    geometry: Mutex<Option<WindowGeometry>>,
+    /// Incremented on every change so that only the last pending save writes.
+    generation: AtomicU64,
+}
+
+fn _window_geometry_path(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::utils::_app_data_dir(app).map(|dir| dir.join("window_state.json"))
+}
+
+pub fn _load_window_geometry(app: &AppHandle) -> Option<WindowGeometry> {
+    let path = _window_geometry_path(app)
+        .map_err(|e| log::error!("Failed to load window state: {}", e))
+        .ok()?;
+    let content = std::fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| log::error!("Failed to parse window state {}: {}", path.display(), e))
+        .ok()
+}
+
+pub fn _save_window_geometry(app: &AppHandle) {
+    let Some(geometry) = app
+        .state::<WindowGeometryState>()
+        .geometry
+        .safe_lock()
+        .clone()
+    else {
+        return;
+    };
+    let path = match _window_geometry_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("Failed to save window state: {}", e);
+            return;
+        }
+    };
+    let result = serde_json::to_string(&geometry)
+        .map_err(std::io::Error::from)
+        .and_then(|content| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, content)
+        });
+    if let Err(e) = result {
+        log::error!("Failed to save window state to {}: {}", path.display(), e);
+    }
+}
+
+/// Records the current geometry of the window and schedules a save.
+pub fn _record_window_geometry(window: &WebviewWindow) {
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let app = window.app_handle();
+    let state = app.state::<WindowGeometryState>();
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+    {
+        let mut geometry = state.geometry.safe_lock();
+        // Keep the last normal bounds while maximized or fullscreen
+        let (position, size) = match geometry.as_ref() {
+            Some(previous) if maximized || fullscreen => (
+                PhysicalPosition::new(previous.x, previous.y),
+                PhysicalSize::new(previous.width, previous.height),
+            ),
+            _ => match (window.outer_position(), window.inner_size()) {
+                (Ok(position), Ok(size)) => (position, size),
+                _ => return,
+            },
+        };
+        *geometry = Some(WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized,
+            fullscreen,
+            monitor: window
+                .current_monitor()
+                .ok()
+                .flatten()
+                .and_then(|monitor| monitor.name().cloned()),
+        });
+    }
+
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        let state = app_handle.state::<WindowGeometryState>();
+        if state.generation.load(Ordering::SeqCst) == generation {
+            _save_window_geometry(&app_handle);
+        }
+    });
+}
+
+fn _overlap(geometry: &WindowGeometry, monitor: &Monitor) -> i64 {
+    let (position, size) = (monitor.position(), monitor.size());
+    let left = geometry.x.max(position.x) as i64;
+    let right =
+        (geometry.x as i64 + geometry.width as i64).min(position.x as i64 + size.width as i64);
+    let top = geometry.y.max(position.y) as i64;
+    let bottom =
+        (geometry.y as i64 + geometry.height as i64).min(position.y as i64 + size.height as i64);
+    (right - left).max(0) * (bottom - top).max(0)
+}
+
+/// Picks the monitor the window was on, falling back to the one it overlaps
+/// most and then the primary monitor, and moves the bounds fully onto it.
+fn _clamp_to_monitors(app: &AppHandle, geometry: &WindowGeometry) -> Option<WindowGeometry> {
+    let monitors = app.available_monitors().ok()?;
+    let monitor = monitors
+        .iter()
+        .filter(|monitor| geometry.monitor.is_some() && monitor.name() == geometry.monitor.as_ref())
+        .max_by_key(|monitor| _overlap(geometry, monitor))
+        .or_else(|| {
+            monitors
+                .iter()
+                .filter(|monitor| _overlap(geometry, monitor) > 0)
+                .max_by_key(|monitor| _overlap(geometry, monitor))
+        })
+        .cloned()
+        .or_else(|| app.primary_monitor().ok().flatten())?;
+
+    let (position, size) = (monitor.position(), monitor.size());
+    let width = geometry.width.min(size.width);
+    let height = geometry.height.min(size.height);
+    Some(WindowGeometry {
+        x: geometry
+            .x
+            .clamp(position.x, position.x + (size.width - width) as i32),
+        y: geometry
+            .y
+            .clamp(position.y, position.y + (size.height - height) as i32),
+        width,
+        height,
+        monitor: monitor.name().cloned(),
+        ..geometry.clone()
+    })
+}
+
+/// Applies the saved geometry to the hidden main window. Returns `false` if
+/// there is nothing to restore.
+pub fn _restore_window_geometry(window: &WebviewWindow) -> bool {
+    let app = window.app_handle();
+    let Some(saved) = _load_window_geometry(app) else {
+        return false;
+    };
+    let Some(geometry) = _clamp_to_monitors(app, &saved) else {
+        log::warn!("No monitors available to restore the window on");
+        return false;
+    };
+    log::debug!("Restoring main window geometry: {:?}", geometry);
+
+    let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+    let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+    if geometry.fullscreen {
+        let _ = window.set_fullscreen(true);
+    } else if geometry.maximized {
+        let _ = window.maximize();
+    }
+    *app.state::<WindowGeometryState>().geometry.safe_lock() = Some(geometry);
+    true
+}
@@ -1,12 +1,13 @@
 //! Window creation and management functions
 
+use crate::error::SafeLock;
 use crate::state::{PendingUpdate, UpdateWindowState, UpdateWindowType};
 use crate::version::{
     DAEMON_BUILD, DAEMON_HASH, DAEMON_VERSION, DESKTOP_BUILD, DESKTOP_HASH, DESKTOP_VERSION,
 };
 use tauri::{
     webview::{DownloadEvent, WebviewWindowBuilder},
-    AppHandle, Emitter, Manager, WebviewUrl,
+    AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow,
 };
 use tauri_plugin_decorum::WebviewWindowExt;
 
@@ -24,15 +25,26 @@ pub const MACOS_TRAFFIC_LIGHTS_INSET_X: f32 = 16.0;
 #[cfg(target_os = "macos")]
 pub const MACOS_TRAFFIC_LIGHTS_INSET_Y: f32 = 16.0;
 
+/// Returns the main window, creating it on first use when the app was started
+/// hidden. `None` if it could not be created.
+pub fn _get_or_create_main_window(app: &AppHandle) -> Option<WebviewWindow> {
+    if let Some(window) = app.get_webview_window("main") {
+        return Some(window);
+    }
+    _setup_main_window(app, crate::utils::_generate_main_url());
+    app.get_webview_window("main")
+}
+
 pub fn _setup_main_window(app: &AppHandle, url: WebviewUrl) {
     log::info!("Setting up main window");
     let win_builder = WebviewWindowBuilder::new(app, "main", url)
         .title("")
         .disable_drag_drop_handler()
         .focused(true)
-        .maximized(true)
+        .visible(false)
         .min_inner_size(800.0, 600.0)
-        .inner_size(1200.0, 720.0);
+        .inner_size(1200.0, 720.0)
+        .on_web_resource_request(crate::csp::_csp_handler(app, "main"));
 
     #[cfg(target_os = "macos")]
     let win_builder = win_builder
@@ -58,31 +70,47 @@ pub fn _setup_main_window(app: &AppHandle, url: WebviewUrl) {
         true
     });
 
-    let _window = win_builder.build().unwrap();
+    let _window = match win_builder.build() {
+        Ok(window) => window,
+        Err(e) => {
+            log::error!("Failed to create main window: {}", e);
+            return;
+        }
+    };
     log::debug!("Main window created successfully");
 
+    // The window is created hidden so that the saved geometry is applied
+    // before it first appears
+    if !crate::window_geometry::_restore_window_geometry(&_window) {
+        let _ = _window.maximize();
+    }
+    let geometry_window = _window.clone();
+    _window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) = event {
+            crate::window_geometry::_record_window_geometry(&geometry_window);
+        }
+    });
+    if let Err(e) = _window.show() {
+        log::error!("Failed to show main window: {}", e);
+    }
+
     // Create a custom titlebar for main window
     // On Windows this hides decoration and creates custom window controls
     // On macOS it needs hiddenTitle: true and titleBarStyle: overlay
-    _window.create_overlay_titlebar().unwrap();
+    if let Err(e) = _window.create_overlay_titlebar() {
+        log::error!("Failed to create main window titlebar: {}", e);
+    }
 
     #[cfg(target_os = "macos")]
     {
         let window_clone = _window.clone();
         let window_clone_2 = _window.clone();
 
-        _window
-            .set_traffic_lights_inset(MACOS_TRAFFIC_LIGHTS_INSET_X, MACOS_TRAFFIC_LIGHTS_INSET_Y)
-            .unwrap();
+        _set_traffic_lights_inset(&_window);
 
         _window.on_window_event(move |event| match event {
             WindowEvent::Resized(_) | WindowEvent::ThemeChanged(_) | WindowEvent::Focused(_) => {
-                window_clone
-                    .set_traffic_lights_inset(
-                        MACOS_TRAFFIC_LIGHTS_INSET_X,
-                        MACOS_TRAFFIC_LIGHTS_INSET_Y,
-                    )
-                    .unwrap();
+                _set_traffic_lights_inset(&window_clone);
             }
             _ => {}
         });
@@ -91,23 +119,39 @@ pub fn _setup_main_window(app: &AppHandle, url: WebviewUrl) {
         // Run 15 times with 1-second intervals. Initialisation will definitely be done by then.
         tauri::async_runtime::spawn(async move {
             for _ in 0..15 {
-                window_clone_2
-                    .set_traffic_lights_inset(
-                        MACOS_TRAFFIC_LIGHTS_INSET_X,
-                        MACOS_TRAFFIC_LIGHTS_INSET_Y,
-                    )
-                    .unwrap();
-                std::thread::sleep(std::time::Duration::from_secs(1));
+                if !_set_traffic_lights_inset(&window_clone_2) {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
             }
         });
     }
 }
 
+/// Returns whether the inset was set, it fails once the window is closed.
+#[cfg(target_os = "macos")]
+fn _set_traffic_lights_inset(window: &WebviewWindow) -> bool {
+    match window
+        .set_traffic_lights_inset(MACOS_TRAFFIC_LIGHTS_INSET_X, MACOS_TRAFFIC_LIGHTS_INSET_Y)
+    {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("Failed to set the traffic lights inset: {}", e);
+            false
+        }
+    }
+}
+
 pub fn _show_about_window(app: &AppHandle) {
     log::info!("Showing about window");
+    if crate::headless::_is_headless(app) {
+        crate::headless::_show_about_dialog(app);
+        return;
+    }
     if let Some(window) = app.get_webview_window("about") {
-        window.show().unwrap();
-        window.set_focus().unwrap();
+        if let Err(e) = window.show().and_then(|_| window.set_focus()) {
+            log::error!("Failed to show about window: {}", e);
+        }
         log::debug!("Reused existing about window");
     } else {
         let desktop_build_encoded = urlencoding::encode(DESKTOP_BUILD);
@@ -126,7 +170,8 @@ pub fn _show_about_window(app: &AppHandle) {
             .focused(true)
             .maximizable(false)
             .minimizable(false)
-            .resizable(false);
+            .resizable(false)
+            .on_web_resource_request(crate::csp::_csp_handler(app, "about"));
 
         #[cfg(target_os = "macos")]
         {
@@ -134,7 +179,10 @@ pub fn _show_about_window(app: &AppHandle) {
                 .hidden_title(true)
                 .title_bar_style(TitleBarStyle::Transparent);
         }
-        let _about_window = about_win_builder.build().unwrap();
+        if let Err(e) = about_win_builder.build() {
+            log::error!("Failed to create about window: {}", e);
+            return;
+        }
         log::debug!("Created new about window");
     }
 }
@@ -148,6 +196,10 @@ pub fn _show_update_window(
     error: String,
     progress: usize,
 ) {
+    if crate::headless::_is_headless(app) {
+        crate::headless::_show_update_dialog(app, update_window_type, &version, &error);
+        return;
+    }
     let window_state = UpdateWindowState {
         update_window_type: update_window_type.clone(),
         version: version.clone(),
@@ -158,27 +210,32 @@ pub fn _show_update_window(
     };
 
     let pending_update_state = app.state::<PendingUpdate>();
-    *pending_update_state
-        .pending_update_window_state
-        .lock()
-        .unwrap() = Some(window_state.clone());
+    *pending_update_state.pending_update_window_state.safe_lock() = Some(window_state.clone());
 
     if let Some(_window) = app.get_webview_window("updates") {
-        app.emit_to("updates", "update-window-state", window_state)
-            .unwrap();
+        if let Err(e) = app.emit_to("updates", "update-window-state", window_state) {
+            log::error!("Failed to send state to update window: {}", e);
+        }
     } else {
         let _update_window =
-            WebviewWindowBuilder::new(app, "updates", WebviewUrl::App("updates/".into()))
+            match WebviewWindowBuilder::new(app, "updates", WebviewUrl::App("updates/".into()))
                 .title("Updates")
                 .inner_size(800.0, 600.0)
                 .focused(true)
                 .decorations(false)
+                .on_web_resource_request(crate::csp::_csp_handler(app, "updates"))
                 .build()
-                .unwrap();
+            {
+                Ok(window) => window,
+                Err(e) => {
+                    log::error!("Failed to create update window: {}", e);
+                    return;
+                }
+            };
 
         #[cfg(target_os = "macos")]
-        {
-            let ns_window = _update_window.ns_window().unwrap() as id;
+        if let Ok(ns_window) = _update_window.ns_window() {
+            let ns_window = ns_window as id;
             unsafe {
                 ns_window.setOpaque_(NO);
                 ns_window.setBackgroundColor_(NSColor::clearColor(nil));